@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn generate(c: &mut Criterion) {
+    let gen = dice_nom::parse("8d6!").unwrap();
+
+    c.bench_function("8d6! naive loop", |b| {
+        b.iter(|| {
+            let mut results = Vec::new();
+            for _ in 0..1000 {
+                results.push(gen.generate(&mut rand::thread_rng()));
+            }
+            results
+        })
+    });
+
+    c.bench_function("8d6! generate_many", |b| {
+        let mut rng = StdRng::seed_from_u64(42);
+        b.iter(|| gen.generate_many(&mut rng, 1000))
+    });
+
+    // every die of a pool is rolled from the one random number generator
+    // passed to `generate`
+    let gen = dice_nom::parse("1000d6").unwrap();
+
+    c.bench_function("1000d6 thread_rng per roll", |b| {
+        b.iter(|| gen.generate(&mut rand::thread_rng()))
+    });
+
+    c.bench_function("1000d6 shared rng", |b| {
+        let mut rng = StdRng::seed_from_u64(42);
+        b.iter(|| gen.generate(&mut rng))
+    });
+}
+
+criterion_group!(benches, generate);
+criterion_main!(benches);
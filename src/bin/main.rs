@@ -2,10 +2,11 @@ extern crate clap;
 use clap::Parser;
 
 use dice_nom::generators::Generator;
-use dice_nom::parsers::generator_parser;
+use dice_nom::results::Results;
+use dice_nom::stats::Histogram;
 
-use std::collections::BTreeMap;
-use std::i32::MAX;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -15,7 +16,7 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[command(version = VERSION)]
 #[command(about = "Generates random dice rolls")]
 struct Args {
-    /// Display the results: full, value, or chart
+    /// Display the results: full, value, json, stats, or chart
     #[arg(short, long)]
     display: Option<String>,
 
@@ -23,6 +24,22 @@ struct Args {
     #[arg(short, long)]
     count: Option<u32>,
 
+    /// Label the chart by percent of the maximum possible total.
+    #[arg(long)]
+    normalize: bool,
+
+    /// Chart the chance of rolling at least each total.
+    #[arg(long)]
+    cumulative: bool,
+
+    /// Leave discarded dice out of the full display.
+    #[arg(long)]
+    hide_dropped: bool,
+
+    /// Seed the random number generator so the rolls can be repeated.
+    #[arg(long)]
+    seed: Option<u64>,
+
     input: String,
 }
 
@@ -32,86 +49,134 @@ fn main() {
     let args = Args::parse();
     let input = args.input;
 
-    let gen = match generator_parser(input.as_ref()) {
-        Ok((_, gen)) => gen,
-        Err(_) => panic!("could not parse `{}`", input),
+    let gen = match dice_nom::parse(input.as_ref()) {
+        Ok(gen) => gen,
+        Err(rest) => panic!("could not parse `{}` at `{}`", input, rest),
+    };
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
     };
 
     match args.display  {
         Some(s) => match s.as_str() {
-            "full" => display_results(&gen, args.count.unwrap_or(1)),
-            "value" => display_value(&gen, args.count.unwrap_or(1)),
-            "chart" => display_chart(&gen, args.count.unwrap_or(10_000)),
-            _ => display_results(&gen, args.count.unwrap_or(1)),
+            "full" => display_results(&gen, &mut rng, args.count.unwrap_or(1), args.hide_dropped),
+            "value" => display_value(&gen, &mut rng, args.count.unwrap_or(1)),
+            "json" => display_json(&gen, &mut rng, args.count.unwrap_or(1)),
+            "chart" if args.normalize => {
+                display_normalized_chart(&gen, &mut rng, args.count.unwrap_or(10_000), args.cumulative)
+            }
+            "chart" => {
+                display_chart(&gen, &mut rng, args.count.unwrap_or(10_000), args.cumulative)
+            }
+            "stats" => display_stats(&gen, &mut rng, args.count.unwrap_or(100_000)),
+            _ => display_results(&gen, &mut rng, args.count.unwrap_or(1), args.hide_dropped),
         },
-        _ => display_results(&gen, args.count.unwrap_or(1)),
+        _ => display_results(&gen, &mut rng, args.count.unwrap_or(1), args.hide_dropped),
     }
 }
 
-fn display_results(gen: &Generator, n: u32) {
-    let mut rng = rand::thread_rng();
+fn display_results(gen: &Generator, rng: &mut StdRng, n: u32, hide_dropped: bool) {
     for _ in 0..n {
-        println!("{}: {}", gen, gen.generate(&mut rng));
+        if hide_dropped {
+            println!("{}: {:#}", gen, gen.generate(rng));
+        } else {
+            println!("{}: {}", gen, gen.generate(rng));
+        }
     }
 }
 
-fn display_value(gen: &Generator, n: u32) {
-    let mut rng = rand::thread_rng();
+fn display_value(gen: &Generator, rng: &mut StdRng, n: u32) {
     for _ in 0..n {
-        println!("{}", gen.generate(&mut rng).sum());
+        println!("{}", gen.generate(rng).sum());
     }
 }
 
-fn display_chart(gen: &Generator, num: u32) {
-    let histo = Histo::build(gen, num);
-
-    let mut cnt = num as f64;
-    let width = if histo.max_cnt < 50 { 1 } else { histo.max_cnt / 50 };
-    for k in histo.min..=histo.max {
-        match histo.map.get(&k) {
-            Some(n) => {
-                print!("{:>3}. {:>5.*}: ", k, 1, (cnt / num as f64) * 100.0);
-                for _ in 0..=(n / width) {
-                    print!("*");
-                }
-                println!();
-                cnt -= *n as f64;
-            }
-            None => {
-                println!("{:>3}. {:>5.*}:", k, 1, 0.0);
-            }
-        }
+fn display_json(gen: &Generator, rng: &mut StdRng, n: u32) {
+    let rolls: Vec<String> = (0..n).map(|_| roll_json(gen, &gen.generate(rng))).collect();
+    if n == 1 {
+        println!("{}", rolls[0]);
+    } else {
+        println!("[{}]", rolls.join(","));
     }
 }
 
-struct Histo {
-    min: i32,
-    max: i32,
-    max_cnt: u32,
-    map: BTreeMap<i32, u32>,
+fn roll_json(gen: &Generator, results: &Results) -> String {
+    let dice: Vec<String> = results
+        .dice()
+        .iter()
+        .map(|d| {
+            format!(
+                "{{\"value\":{},\"kept\":{},\"bonus\":{},\"hit\":{}}}",
+                d.value, d.kept, d.bonus, d.hit
+            )
+        })
+        .collect();
+    format!(
+        "{{\"expression\":\"{}\",\"total\":{},\"dice\":[{}],\"value\":{}}}",
+        escape_json(&gen.to_string()),
+        results.sum(),
+        dice.join(","),
+        results.lhs.value()
+    )
 }
 
-impl Histo {
-    pub fn build(gen: &Generator, count: u32) -> Histo {
-        let mut histo = Histo{ min: MAX, max: 0, max_cnt: 0, map: BTreeMap::new() };
-        let mut rng = rand::thread_rng();
-        for _ in 0..count {
-            let v = gen.generate(&mut rng).sum();
-            if v < histo.min { histo.min = v; }
-            if v > histo.max { histo.max = v; }
-            match histo.map.get(&v) {
-                Some(n) => {
-                    let cnt = n + 1;
-                    if cnt > histo.max_cnt {
-                        histo.max_cnt = cnt;
-                    }
-                    histo.map.insert(v, cnt);
-                }
-                None => {
-                    histo.map.insert(v, 1);
-                }
-            }
+/// escape_json escapes the characters that may not appear as-is in a
+/// JSON string.
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
         }
-        histo
+    }
+    escaped
+}
+
+fn display_stats(gen: &Generator, rng: &mut StdRng, num: u32) {
+    let histo = Histogram::build(gen, num, rng);
+    let (mean, std_dev) = match (histo.mean(), histo.std_dev()) {
+        (Some(mean), Some(std_dev)) => (mean, std_dev),
+        _ => return,
+    };
+
+    println!(
+        "{:>8} {:>8} {:>8} {:>6} {:>6} {:>6} {:>6}",
+        "mean", "median", "std dev", "min", "p10", "p90", "max"
+    );
+    println!(
+        "{:>8.2} {:>8} {:>8.2} {:>6} {:>6} {:>6} {:>6}",
+        mean,
+        histo.median().unwrap_or(0),
+        std_dev,
+        histo.min().unwrap_or(0),
+        histo.percentile(10.0).unwrap_or(0),
+        histo.percentile(90.0).unwrap_or(0),
+        histo.max().unwrap_or(0),
+    );
+}
+
+fn display_chart(gen: &Generator, rng: &mut StdRng, num: u32, cumulative: bool) {
+    let histo = Histogram::build(gen, num, rng);
+    if cumulative {
+        print!("{}", histo.render_cumulative(50));
+    } else {
+        print!("{}", histo.render(50));
+    }
+}
+
+fn display_normalized_chart(gen: &Generator, rng: &mut StdRng, num: u32, cumulative: bool) {
+    let histo = Histogram::build(gen, num, rng);
+
+    // unbounded expressions fall back to the largest total rolled
+    let max = gen.max_possible().or(histo.max()).unwrap_or(1);
+    if cumulative {
+        print!("{}", histo.render_normalized_cumulative(max, 50));
+    } else {
+        print!("{}", histo.render_normalized(max, 50));
     }
 }
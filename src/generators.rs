@@ -1,13 +1,131 @@
-use super::results::{Pool, Results, Value};
+use super::results::{Comparison, ConditionResults, Decimal, Pool, Results, RoundingMode, Value};
 use rand::prelude::*;
 use std::fmt;
-use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// MAX_ITERATIONS is the most times an open-ended operator (one that keeps
+/// rolling until some condition fails) will roll a single die before it stops.
+/// The pool-wide `!!` counts each round of rerolling the whole pool as one
+/// iteration. Without the cap a threshold that every face meets (`d6!!1`, or
+/// the default threshold on a `d1`) would never stop rolling.
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::generators::MAX_ITERATIONS;
+/// let mut rng = rand::thread_rng();
+/// let pool = dice_nom::parse("d6!!1").unwrap().generate(&mut rng).lhs;
+/// assert_eq!(pool.count(), MAX_ITERATIONS + 1);
+///
+/// let pool = dice_nom::parse("2d1**").unwrap().generate(&mut rng).lhs;
+/// assert_eq!(pool.count(), 2 * (MAX_ITERATIONS + 1));
+/// ```
+pub const MAX_ITERATIONS: usize = 1000;
+
+/// GenError is why a roll was stopped before it finished.
+#[derive(Debug, PartialEq)]
+pub enum GenError {
+    /// more values were rolled than the bound given to `generate_bounded`
+    TooManyDice(usize),
+}
 
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenError::TooManyDice(n) => write!(f, "rolled more than {} dice", n),
+        }
+    }
+}
 
-#[derive(Debug, PartialEq)]
+impl std::error::Error for GenError {}
+
+/// Budget counts the values rolled so far against the most allowed.
+pub(crate) struct Budget {
+    max: usize,
+    used: usize,
+}
+
+impl Budget {
+    fn new(max: usize) -> Budget {
+        Budget { max, used: 0 }
+    }
+
+    fn unbounded() -> Budget {
+        Budget::new(usize::MAX)
+    }
+
+    fn remaining(&self) -> usize {
+        self.max.saturating_sub(self.used)
+    }
+
+    fn spend(&mut self, n: usize) -> Result<(), GenError> {
+        self.used = self.used.saturating_add(n);
+        if self.used > self.max {
+            Err(GenError::TooManyDice(self.max))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// unbounded unwraps a roll made with `Budget::unbounded`, which can not
+/// run out: there are never more than `usize::MAX` values in memory.
+fn unbounded<T>(result: Result<T, GenError>) -> T {
+    result.expect("an unbounded roll can not run out of dice")
+}
+
+/// explosion_limit is the most times a die may explode under an explicit
+/// limit, e.g. the 3 of `3d6!!:3`; it is never more than `MAX_ITERATIONS`.
+///
+/// * Examples
+///
+/// ```
+/// let mut rng = rand::thread_rng();
+/// // a d1 always explodes, so it stops exactly at the limit
+/// let gen = dice_nom::parse("3d1**:3").unwrap();
+/// assert_eq!(format!("{}", gen), "3d1**:3");
+/// assert_eq!(gen.generate(&mut rng).lhs.count(), 3 * 4);
+///
+/// let gen = dice_nom::parse("2d1!!:2").unwrap();
+/// assert_eq!(gen.generate(&mut rng).lhs.count(), 2 * 3);
+///
+/// let gen = dice_nom::parse("1d1!c:5").unwrap();
+/// assert_eq!(gen.generate(&mut rng).sum(), 6);
+///
+/// // a limit of 0 turns the explosion off
+/// let gen = dice_nom::parse("4d1*:0").unwrap();
+/// assert_eq!(gen.generate(&mut rng).lhs.count(), 4);
+/// ```
+fn explosion_limit(n: i32) -> usize {
+    (n.max(0) as usize).min(MAX_ITERATIONS)
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Generator {
     pub succ: SuccGenerator,
     pub op: Option<ComparisonOp>,
+
+    /// the text this generator was parsed from, exactly as given to
+    /// `dice_nom::parse`; `Display` rebuilds the expression instead
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub source: Option<String>,
+}
+
+/// Generators are equal when they roll the same way, whatever text they
+/// were parsed from.
+///
+/// * Examples
+///
+/// ```
+/// let gen = dice_nom::parse("2d6  1d4").unwrap();
+/// assert_eq!(format!("{}", gen), "2d6 1d4");
+/// assert_eq!(dice_nom::parse(&format!("{}", gen)).unwrap(), gen);
+/// ```
+impl PartialEq for Generator {
+    fn eq(&self, other: &Self) -> bool {
+        self.succ == other.succ && self.op == other.op
+    }
 }
 
 impl fmt::Display for Generator {
@@ -36,72 +154,291 @@ impl Generator {
     ///                 terms: vec![ArithTermGenerator{
     ///                     op: ArithOp::ImplicitAdd,
     ///                     term: TermGenerator::Pool(PoolGenerator{
-    ///                         count: 12,
+    ///                         count: Count::Fixed(12),
     ///                         range: 6,
-    ///                         op: None
-    ///                     })
+    ///                         die: Die::Standard,
+    ///                         ops: vec![]
+    ///                     }),
+    ///                     label: None
     ///                 }]
     ///             },
     ///             op: None
     ///         },
     ///         op: None
     ///     },
-    ///     op: None
+    ///     op: None,
+    ///     source: None
     /// };
     /// let mut rng = rand::thread_rng();
     /// let pool = gen.generate(&mut rng);
+    ///
+    /// // the same seed always produces the same results
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("4d6! ^3 + 1d8** > 3d10").unwrap();
+    /// let first = gen.generate(&mut StdRng::seed_from_u64(42));
+    /// let second = gen.generate(&mut StdRng::seed_from_u64(42));
+    /// assert_eq!(format!("{}", first), format!("{}", second));
+    /// assert_eq!(first.sum(), second.sum());
     /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Results {
-        let lhs = self.succ.generate(rng);
-        let (rhs, value) = match &self.op {
-            Some(op) => match op {
-                ComparisonOp::GT(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = if lhs.value() > rhs.value() { 1 } else { 0 };
-                    (Some(rhs), val)
-                }
-
-                ComparisonOp::GE(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = if lhs.value() >= rhs.value() { 1 } else { 0 };
-                    (Some(rhs), val)
-                }
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
 
-                ComparisonOp::LT(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = if lhs.value() < rhs.value() { 1 } else { 0 };
-                    (Some(rhs), val)
-                }
+    /// generate_bounded is `generate` for untrusted expressions: it stops
+    /// with `GenError::TooManyDice` as soon as more than `max_dice` values
+    /// (dice, bonus dice, rerolls and constants, on both sides of a
+    /// comparison) have been rolled, instead of building a huge pool.
+    ///
+    /// * Example
+    ///
+    /// ```
+    /// use dice_nom::generators::GenError;
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("4d6 + 2 > 3d6").unwrap();
+    /// assert_eq!(gen.generate_bounded(&mut rng, 8).unwrap().lhs.count(), 5);
+    /// assert_eq!(gen.generate_bounded(&mut rng, 7).err(), Some(GenError::TooManyDice(7)));
+    ///
+    /// // every die explodes, so this would roll about a million dice
+    /// let gen = dice_nom::parse("999d6!!1").unwrap();
+    /// assert!(gen.generate_bounded(&mut rng, 10_000).is_err());
+    ///
+    /// // so would a pool that is just too big
+    /// let gen = dice_nom::parse("1000d6").unwrap();
+    /// assert!(gen.generate_bounded(&mut rng, 500).is_err());
+    ///
+    /// // rerolls count as dice rolled even though they replace a die
+    /// let gen = dice_nom::parse("1d6b50000000k1").unwrap();
+    /// assert!(gen.generate_bounded(&mut rng, 100).is_err());
+    /// let gen = dice_nom::parse("1000d6b1000k1").unwrap();
+    /// assert!(gen.generate_bounded(&mut rng, 10_000).is_err());
+    /// let gen = dice_nom::parse("1d6b3k1").unwrap();
+    /// assert!(gen.generate_bounded(&mut rng, 3).is_ok());
+    /// assert!(gen.generate_bounded(&mut rng, 2).is_err());
+    /// ```
+    pub fn generate_bounded<R: Rng + ?Sized>(&self, rng: &mut R, max_dice: usize) -> Result<Results, GenError> {
+        self.generate_within(rng, &mut Budget::new(max_dice))
+    }
 
-                ComparisonOp::LE(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = if lhs.value() <= rhs.value() { 1 } else { 0 };
-                    (Some(rhs), val)
-                }
+    fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Results, GenError> {
+        let lhs = self.succ.generate_within(rng, budget)?;
+        let rhs = match &self.op {
+            Some(op) => Some(op.succ().generate_within(rng, budget)?),
+            None => None,
+        };
+        Ok(self.results(lhs, rhs))
+    }
 
-                ComparisonOp::EQ(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = if lhs.value() == rhs.value() { 1 } else { 0 };
-                    (Some(rhs), val)
-                }
+    /// generate_streams rolls the generator with a random number generator
+    /// of its own for every term, seeded from `seed`, the number of the
+    /// `roll` and the position of the term (counting on from the left hand
+    /// side into the right). The same seed and roll always produce the same
+    /// results, so rolls can be shared out between threads in any order,
+    /// and a term rolls the same dice whatever else is in the expression.
+    ///
+    /// * Example
+    ///
+    /// ```
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("4d6! ^3 + 1d8** > 3d10").unwrap();
+    /// let first = gen.generate_streams::<StdRng>(42, 7);
+    /// assert_eq!(first, gen.generate_streams::<StdRng>(42, 7));
+    ///
+    /// // each term has its own stream
+    /// let one = dice_nom::parse("3d6").unwrap().generate_streams::<StdRng>(42, 0);
+    /// let two = dice_nom::parse("3d6 + 2d20").unwrap().generate_streams::<StdRng>(42, 0);
+    /// assert_eq!(one.lhs.values[..], two.lhs.values[..3]);
+    ///
+    /// let rolls: Vec<i32> = (0..20).map(|n| gen.generate_streams::<StdRng>(42, n).lhs.sum()).collect();
+    /// assert!(rolls.iter().any(|&sum| sum != rolls[0]));
+    /// ```
+    pub fn generate_streams<R: SeedableRng + Rng>(&self, seed: u64, roll: u64) -> Results {
+        let mut stream = 0u64;
+        let mut roll_side = |succ: &SuccGenerator| {
+            let pool = succ.hits.expr.roll_terms(|t| {
+                let mut rng = R::seed_from_u64(stream_seed(seed, roll, stream));
+                stream += 1;
+                t.generate_within(&mut rng, &mut Budget::unbounded())
+            });
+            succ.score(succ.hits.score(unbounded(pool)))
+        };
+        let lhs = roll_side(&self.succ);
+        let rhs = self.op.as_ref().map(|op| roll_side(op.succ()));
+        self.results(lhs, rhs)
+    }
 
-                ComparisonOp::CMP(rhs) => {
-                    let rhs = rhs.generate(rng);
-                    let val = match lhs.value().cmp(&rhs.value()) {
-                        Ordering::Less => -1,
-                        Ordering::Greater => 1,
-                        Ordering::Equal => 0,
-                    };
-                    (Some(rhs), val)
-                }
-            },
-            None => (None, 0),
+    fn results(&self, lhs: Pool, rhs: Option<Pool>) -> Results {
+        let op = self.op.as_ref().map(|op| op.kind());
+        let value = match (&op, &rhs) {
+            (Some(op), Some(rhs)) => op.compare(lhs.value(), rhs.value()),
+            _ => 0,
         };
-        Results { lhs, rhs, value }
+        Results { lhs, rhs, value, op }
+    }
+
+    /// generate_many rolls the generator `n` times with the same random
+    /// number generator.
+    ///
+    /// * Example
+    ///
+    /// ```
+    /// use rand::prelude::*;
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("8d6!").unwrap();
+    /// let results = gen.generate_many(&mut StdRng::seed_from_u64(42), 100);
+    /// assert_eq!(results.len(), 100);
+    /// assert!(results.iter().all(|r| r.sum() >= 8));
+    /// ```
+    pub fn generate_many<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<Results> {
+        let mut results = Vec::with_capacity(n);
+        for _ in 0..n {
+            results.push(self.generate(rng));
+        }
+        results
+    }
+
+    /// iter lazily rolls the generator for as long as it is asked to.
+    ///
+    /// * Example
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("1d20").unwrap();
+    /// assert_eq!(gen.iter(&mut rng).take(5).count(), 5);
+    ///
+    /// // keep rolling until a natural 20
+    /// let rolls = gen.iter(&mut rng).take_while(|r| r.sum() < 20).count();
+    /// assert!(rolls < 10_000);
+    /// ```
+    pub fn iter<'a, R: Rng + ?Sized>(&'a self, rng: &'a mut R) -> impl Iterator<Item = Results> + 'a {
+        std::iter::repeat_with(move || self.generate(rng))
+    }
+
+    /// depth counts the layers of comparison and success operators in the
+    /// generator so overly complex expressions can be rejected.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// assert_eq!(dice_nom::parse("2d6").unwrap().depth(), 0);
+    /// assert_eq!(dice_nom::parse("2d6 > 1d8").unwrap().depth(), 1);
+    /// assert_eq!(dice_nom::parse("2d6 {7}").unwrap().depth(), 1);
+    /// assert_eq!(dice_nom::parse("2d6 > 1d8 {4}").unwrap().depth(), 2);
+    /// ```
+    pub fn depth(&self) -> usize {
+        let lhs = self.succ.depth();
+        match &self.op {
+            Some(op) => 1 + lhs.max(op.succ().depth()),
+            None => lhs,
+        }
+    }
+
+    /// with_rounding sets how every plain `/` in the generator rounds. A
+    /// `/` rounds toward zero unless it is changed here; a division that
+    /// picks its own rounding (`\\`, `\\^` or `\\~`) keeps it.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::RoundingMode;
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("7 / 2").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).sum(), 3);
+    ///
+    /// let gen = gen.with_rounding(RoundingMode::Up);
+    /// assert_eq!(format!("{}", gen), "7 \\^ 2");
+    /// assert_eq!(gen.generate(&mut rng).sum(), 4);
+    ///
+    /// // divisions with their own rounding are left alone
+    /// let gen = dice_nom::parse("(1d1 + 6) \\ 2 > 9 / 2").unwrap();
+    /// let gen = gen.with_rounding(RoundingMode::Nearest);
+    /// assert_eq!(format!("{}", gen), "(1d1 + 6) \\ 2 > 9 \\~ 2");
+    /// let results = gen.generate(&mut rng);
+    /// assert_eq!(results.lhs.value(), 3);
+    /// assert_eq!(results.rhs.unwrap().value(), 5);
+    /// ```
+    pub fn with_rounding(mut self, mode: RoundingMode) -> Generator {
+        self.succ.hits.expr.set_rounding(mode);
+        if let Some(op) = &mut self.op {
+            op.succ_mut().hits.expr.set_rounding(mode);
+        }
+        self
+    }
+}
+
+/// Condition combines the outcomes of several generators: `&` passes when
+/// both sides pass and `|` when either does. A generator passes when its
+/// sum is more than 0, e.g. a comparison that succeeded. `&` binds tighter
+/// than `|`.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Condition {
+    Roll(Generator),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Roll(gen) => write!(f, "{}", gen),
+            Condition::And(lhs, rhs) => write!(f, "{} & {}", lhs, rhs),
+            Condition::Or(lhs, rhs) => write!(f, "{} | {}", lhs, rhs),
+        }
+    }
+}
+
+impl Condition {
+    /// generate rolls every generator in the condition (none are skipped,
+    /// so each can be displayed) and combines whether they passed.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let cond = dice_nom::parse_condition("2d6 >= 2 & 1d20 >= 1").unwrap();
+    /// let results = cond.generate(&mut rng);
+    /// assert!(results.passed);
+    /// assert_eq!(results.sum(), 1);
+    /// assert_eq!(results.results.len(), 2);
+    ///
+    /// let cond = dice_nom::parse_condition("2d6 >= 2 & 1d20 > 20").unwrap();
+    /// let results = cond.generate(&mut rng);
+    /// assert!(!results.passed);
+    /// assert_eq!(results.results[0].sum(), 1);
+    /// assert_eq!(results.results[1].sum(), 0);
+    ///
+    /// let cond = dice_nom::parse_condition("1d20 > 20 | 2d6 >= 2").unwrap();
+    /// assert!(cond.generate(&mut rng).passed);
+    /// assert_eq!(format!("{}", cond), "1d20 > 20 | 2d6 >= 2");
+    /// ```
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> ConditionResults {
+        let mut results = vec![];
+        let passed = self.evaluate(rng, &mut results);
+        ConditionResults { passed, results }
+    }
+
+    fn evaluate<R: Rng + ?Sized>(&self, rng: &mut R, results: &mut Vec<Results>) -> bool {
+        match self {
+            Condition::Roll(gen) => {
+                let roll = gen.generate(rng);
+                let passed = roll.sum() > 0;
+                results.push(roll);
+                passed
+            }
+            Condition::And(lhs, rhs) => {
+                let lhs = lhs.evaluate(rng, results);
+                rhs.evaluate(rng, results) && lhs
+            }
+            Condition::Or(lhs, rhs) => {
+                let lhs = lhs.evaluate(rng, results);
+                rhs.evaluate(rng, results) || lhs
+            }
+        }
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComparisonOp {
     GT(SuccGenerator),
     GE(SuccGenerator),
@@ -111,6 +448,43 @@ pub enum ComparisonOp {
     CMP(SuccGenerator),
 }
 
+impl ComparisonOp {
+    /// succ returns the right hand side of the comparison.
+    pub fn succ(&self) -> &SuccGenerator {
+        match self {
+            ComparisonOp::GT(succ)
+            | ComparisonOp::GE(succ)
+            | ComparisonOp::LT(succ)
+            | ComparisonOp::LE(succ)
+            | ComparisonOp::EQ(succ)
+            | ComparisonOp::CMP(succ) => succ,
+        }
+    }
+
+    /// kind returns the comparison without its right hand side.
+    pub fn kind(&self) -> Comparison {
+        match self {
+            ComparisonOp::GT(_) => Comparison::GT,
+            ComparisonOp::GE(_) => Comparison::GE,
+            ComparisonOp::LT(_) => Comparison::LT,
+            ComparisonOp::LE(_) => Comparison::LE,
+            ComparisonOp::EQ(_) => Comparison::EQ,
+            ComparisonOp::CMP(_) => Comparison::CMP,
+        }
+    }
+
+    fn succ_mut(&mut self) -> &mut SuccGenerator {
+        match self {
+            ComparisonOp::GT(succ)
+            | ComparisonOp::GE(succ)
+            | ComparisonOp::LT(succ)
+            | ComparisonOp::LE(succ)
+            | ComparisonOp::EQ(succ)
+            | ComparisonOp::CMP(succ) => succ,
+        }
+    }
+}
+
 impl fmt::Display for ComparisonOp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -125,6 +499,7 @@ impl fmt::Display for ComparisonOp {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SuccGenerator {
     pub hits: HitsGenerator,
     pub op: Option<SuccessOp>,
@@ -141,28 +516,72 @@ impl fmt::Display for SuccGenerator {
 }
 
 impl SuccGenerator {
+    fn depth(&self) -> usize {
+        match &self.op {
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
     /// generate builds a generator that calculates success based on whether
-    /// the pool sum is greater than the target number.
+    /// the pool sum is greater than the target number, or on how far each
+    /// kept die got past a step.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// // an 8 and a 5 with a step of 4 are 2 + 1 successes
+    /// let gen = dice_nom::parse("1d8w[0,0,0,0,0,0,0,1] + 1d8w[0,0,0,0,1,0,0,0] {{4}}").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).sum(), 3);
+    ///
+    /// // the first success takes 4, every one after takes 2 more
+    /// let gen = dice_nom::parse("1d8w[0,0,0,0,0,0,0,1] {{4, 2}}").unwrap();
+    /// assert_eq!(format!("{}", gen), "1d8w[0,0,0,0,0,0,0,1]{{4, 2}}");
+    /// assert_eq!(gen.generate(&mut rng).sum(), 3);
+    /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
-        let mut pool = self.hits.generate(rng);
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
+        Ok(self.score(self.hits.generate_within(rng, budget)?))
+    }
+
+    /// score sets the successes of the rolled pool as its value
+    fn score(&self, mut pool: Pool) -> Pool {
         match &self.op {
             Some(op) => match op {
                 SuccessOp::TargetSucc(n) => {
-                    if pool.sum() >= *n {
-                        pool.set_value(pool.sum() - n + 1);
+                    if pool.value() >= *n {
+                        pool.set_value(pool.value() - n + 1);
                     } else {
                         pool.set_value(0);
                     }
                     pool
                 }
                 SuccessOp::TargetSuccNext(n, m) => {
-                    if pool.sum() >= *n {
-                        pool.set_value(((pool.sum() - n) / m) + 1);
+                    if pool.value() >= *n {
+                        pool.set_value(((pool.value() - n) / m) + 1);
                     } else {
                         pool.set_value(0);
                     }
                     pool
                 }
+                SuccessOp::Tiers(tiers) => {
+                    pool.set_value(SuccessOp::tier(tiers, pool.value()));
+                    pool
+                }
+                SuccessOp::PerDie(step, increment) => {
+                    let successes = pool
+                        .values
+                        .iter()
+                        .filter(|v| v.is_random() && !v.is_discarded())
+                        .map(|v| SuccessOp::per_die(v.sum(), *step, *increment))
+                        .sum();
+                    pool.set_value(successes);
+                    pool
+                }
             },
             None => pool,
         }
@@ -170,9 +589,12 @@ impl SuccGenerator {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SuccessOp {
     TargetSucc(i32),
     TargetSuccNext(i32, i32),
+    PerDie(i32, Option<i32>),
+    Tiers(Vec<i32>),
 }
 
 impl fmt::Display for SuccessOp {
@@ -180,11 +602,81 @@ impl fmt::Display for SuccessOp {
         match self {
             SuccessOp::TargetSucc(n) => write!(f, "{{{}}}", n),
             SuccessOp::TargetSuccNext(n, m) => write!(f, "{{{}, {}}}", n, m),
+            SuccessOp::PerDie(n, None) => write!(f, "{{{{{}}}}}", n),
+            SuccessOp::PerDie(n, Some(m)) => write!(f, "{{{{{}, {}}}}}", n, m),
+            SuccessOp::Tiers(tiers) => {
+                let tiers: Vec<String> = tiers.iter().map(|n| n.to_string()).collect();
+                // fewer than three thresholds need a trailing comma to tell
+                // them from `{n}` and `{n, m}`
+                match tiers.len() {
+                    0..=2 => write!(f, "{{{},}}", tiers.join(", ")),
+                    _ => write!(f, "{{{}}}", tiers.join(", ")),
+                }
+            }
         }
     }
 }
 
+impl SuccessOp {
+    /// tier is the number of thresholds the sum meets, so a sum below
+    /// every threshold is tier 0.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::SuccessOp;
+    /// assert_eq!(SuccessOp::tier(&[7, 10], 8), 1);
+    /// assert_eq!(SuccessOp::tier(&[7, 10], 6), 0);
+    /// assert_eq!(SuccessOp::tier(&[7, 10, 12], 12), 3);
+    ///
+    /// // 6- fails, 7-9 is a partial success and 10+ a full success
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("2d6{7, 10,}").unwrap();
+    /// assert_eq!(gen.succ.op, Some(SuccessOp::Tiers(vec![7, 10])));
+    /// for _ in 0..20 {
+    ///     let results = gen.generate(&mut rng);
+    ///     let sum = results.lhs.sum();
+    ///     let tier = if sum >= 10 { 2 } else if sum >= 7 { 1 } else { 0 };
+    ///     assert_eq!(results.sum(), tier);
+    /// }
+    ///
+    /// let gen = dice_nom::parse("2d6{7, 10, 12}").unwrap();
+    /// assert_eq!(gen.succ.op, Some(SuccessOp::Tiers(vec![7, 10, 12])));
+    /// assert_eq!(format!("{}", gen), "2d6{7, 10, 12}");
+    /// ```
+    pub fn tier(tiers: &[i32], sum: i32) -> i32 {
+        tiers.iter().filter(|&&n| sum >= n).count() as i32
+    }
+
+    /// per_die is the number of successes a single die with the given sum
+    /// scores: one for every `step`, or one for the first `step` and one
+    /// for every `increment` after. A subtracted die takes its successes
+    /// away.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::SuccessOp;
+    /// assert_eq!(SuccessOp::per_die(8, 4, None), 2);
+    /// assert_eq!(SuccessOp::per_die(5, 4, None), 1);
+    /// assert_eq!(SuccessOp::per_die(3, 4, None), 0);
+    /// assert_eq!(SuccessOp::per_die(10, 4, Some(3)), 3);
+    /// assert_eq!(SuccessOp::per_die(-8, 4, None), -2);
+    /// ```
+    pub fn per_die(sum: i32, step: i32, increment: Option<i32>) -> i32 {
+        let step = step.max(1);
+        let n = sum.saturating_abs();
+        let successes = match increment {
+            None => n / step,
+            Some(_) if n < step => 0,
+            Some(m) => (n - step) / m.max(1) + 1,
+        };
+        successes * sum.signum()
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HitsGenerator {
     pub expr: ExprGenerator,
     pub op: Option<TargetOp>,
@@ -214,10 +706,12 @@ impl HitsGenerator {
     ///         terms: vec![ArithTermGenerator{
     ///             op: ArithOp::ImplicitAdd,
     ///             term: TermGenerator::Pool(PoolGenerator{
-    ///                 count: 12,
+    ///                 count: Count::Fixed(12),
     ///                 range: 6,
-    ///                 op: None,
-    ///             })
+    ///                 die: Die::Standard,
+    ///                 ops: vec![],
+    ///             }),
+    ///             label: None
     ///         }]
     ///     },
     ///     op: Some(TargetOp::TargetHigh(4))
@@ -228,33 +722,28 @@ impl HitsGenerator {
     /// assert!(pool.hits() > 0);
     /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
-        let mut pool = self.expr.generate(rng);
-        match &self.op {
-            Some(op) => match op {
-                TargetOp::TargetHigh(n) => {
-                    for idx in 0..pool.count() {
-                        let b = pool.values[idx].sum().abs() >= *n;
-                        pool.values[idx].set_hit(b);
-                    }
-                    pool
-                }
-                TargetOp::TargetLow(n) => {
-                    for idx in 0..pool.count() {
-                        let b = pool.values[idx].sum().abs() <= *n;
-                        pool.values[idx].set_hit(b);
-                    }
-                    pool
-                }
-            },
-            None => pool,
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
+        Ok(self.score(self.expr.generate_within(rng, budget)?))
+    }
+
+    /// score marks the hits of the rolled pool
+    fn score(&self, mut pool: Pool) -> Pool {
+        if let Some(op) = &self.op {
+            op.apply(&mut pool);
         }
+        pool
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TargetOp {
     TargetHigh(i32),
     TargetLow(i32),
+    TargetHighBotch(i32),
 }
 
 impl fmt::Display for TargetOp {
@@ -262,18 +751,73 @@ impl fmt::Display for TargetOp {
         match self {
             TargetOp::TargetHigh(n) => write!(f, "[{}]", n),
             TargetOp::TargetLow(n) => write!(f, "({})", n),
+            TargetOp::TargetHighBotch(n) => write!(f, "[{}]b", n),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl TargetOp {
+    /// apply marks the hits in the pool. `TargetHighBotch` also subtracts
+    /// one for every die that rolled a 1 and sets the net successes as the
+    /// value of the pool.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::TargetOp;
+    /// use dice_nom::results::Pool;
+    /// let mut pool = Pool::from_faces(&[(1, 10), (8, 10), (1, 10), (9, 10), (8, 10)]);
+    /// TargetOp::TargetHighBotch(8).apply(&mut pool);
+    /// assert_eq!(pool.hits(), 3);
+    /// assert_eq!(pool.value(), 1);
+    ///
+    /// let gen = dice_nom::parse("10d10[8]b").unwrap();
+    /// assert_eq!(format!("{}", gen), "10d10[8]b");
+    /// let results = gen.generate(&mut rand::thread_rng());
+    /// let ones = results.lhs.values.iter().filter(|v| v.value == 1).count() as i32;
+    /// assert_eq!(results.sum(), results.lhs.hits() as i32 - ones);
+    /// ```
+    pub fn apply(&self, pool: &mut Pool) {
+        match self {
+            TargetOp::TargetHigh(n) | TargetOp::TargetHighBotch(n) => {
+                for idx in 0..pool.count() {
+                    let b = pool.values[idx].sum().abs() >= *n;
+                    pool.values[idx].set_hit(b);
+                }
+            }
+            TargetOp::TargetLow(n) => {
+                for idx in 0..pool.count() {
+                    let b = pool.values[idx].sum().abs() <= *n;
+                    pool.values[idx].set_hit(b);
+                }
+            }
+        }
+
+        if let TargetOp::TargetHighBotch(_) = self {
+            let botches: i32 = pool
+                .values
+                .iter()
+                .filter(|v| v.is_random() && !v.is_discarded() && v.value == 1)
+                .map(|v| v.multiplier())
+                .sum();
+            pool.set_value(pool.sum() - botches);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExprGenerator {
     pub terms: Vec<ArithTermGenerator>,
 }
 
 impl fmt::Display for ExprGenerator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for t in self.terms.iter() {
+        for (idx, t) in self.terms.iter().enumerate() {
+            // without a space `2d6 1d4` would read back as `2d61 d4`
+            if idx > 0 && t.op == ArithOp::ImplicitAdd {
+                write!(f, " ")?;
+            }
             write!(f, "{}", t)?;
         }
         write!(f, "")
@@ -281,20 +825,109 @@ impl fmt::Display for ExprGenerator {
 }
 
 impl ExprGenerator {
+    /// generate rolls every term into a single pool. Multiplication and
+    /// division bind tighter than addition and subtraction, so the value of
+    /// `2d6 + 3 * 2` is the 2d6 plus 6. When either is used (or a term has
+    /// a value of its own, e.g. counted matches) the result is set as the
+    /// value of the pool; the dice are all still listed.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let (_, expr) = dice_nom::parsers::expr_parser("2d6 * 3").unwrap();
+    /// let pool = expr.generate(&mut rng);
+    /// assert_eq!(pool.count(), 3);
+    /// assert_eq!(pool.value(), (pool.sum() - 3) * 3);
+    ///
+    /// let (_, expr) = dice_nom::parsers::expr_parser("1 + 3 * 4 - 10 / 3").unwrap();
+    /// assert_eq!(expr.generate(&mut rng).value(), 10);
+    ///
+    /// // division rounds toward zero and dividing by zero counts as 0
+    /// let (_, expr) = dice_nom::parsers::expr_parser("2 - 7 / 2").unwrap();
+    /// assert_eq!(expr.generate(&mut rng).value(), -1);
+    /// let (_, expr) = dice_nom::parsers::expr_parser("7 / 0").unwrap();
+    /// assert_eq!(expr.generate(&mut rng).value(), 0);
+    ///
+    /// // halved rounding down and rounding up
+    /// let (_, expr) = dice_nom::parsers::expr_parser("(1d1 + 2) \\ 2").unwrap();
+    /// assert_eq!(expr.generate(&mut rng).value(), 1);
+    /// let (_, expr) = dice_nom::parsers::expr_parser("(1d1 + 2) \\^ 2").unwrap();
+    /// assert_eq!(expr.generate(&mut rng).value(), 2);
+    /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn set_rounding(&mut self, mode: RoundingMode) {
+        for t in self.terms.iter_mut() {
+            if t.op == ArithOp::Div(RoundingMode::TowardZero) {
+                t.op = ArithOp::Div(mode);
+            }
+            t.term.set_rounding(mode);
+        }
+    }
+
+    pub(crate) fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
+        self.roll_terms(|t| t.generate_within(rng, budget))
+    }
+
+    /// roll_terms rolls each term with `roll` and combines them into a
+    /// single pool
+    fn roll_terms<F>(&self, mut roll: F) -> Result<Pool, GenError>
+    where
+        F: FnMut(&ArithTermGenerator) -> Result<Pool, GenError>,
+    {
         let mut pool = Pool::new();
+        let mut total = 0i32;
+        let mut product = 0i32;
+        let mut has_value = false;
         for t in self.terms.iter() {
-            pool.values.append(&mut t.generate(rng).values);
+            let mut term = roll(t)?;
+            has_value |= term.has_value();
+            let value = term.value();
+            product = match t.op {
+                ArithOp::Mul => {
+                    has_value = true;
+                    product.saturating_mul(value)
+                }
+                ArithOp::Div(_) => {
+                    has_value = true;
+                    t.op.divide(product, value)
+                }
+                _ => {
+                    total = total.saturating_add(product);
+                    value
+                }
+            };
+            let start = pool.count();
+            pool.append(&mut term);
+            if let Some(label) = &t.label {
+                pool.push_label(label, start..pool.count());
+            }
+            pool.push_term(t.to_string().trim(), start..pool.count(), value);
         }
-        pool
+        total = total.saturating_add(product);
+
+        // a value set on any term (e.g. counted matches) replaces that
+        // term's sum in the value of the whole expression
+        if has_value {
+            pool.set_value(total);
+        }
+        Ok(pool)
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ArithOp {
     ImplicitAdd,
     Add,
     Sub,
+    Mul,
+    /// division rounded by the mode: `/` toward zero, `\\` down, `\\^` up
+    /// and `\\~` to the nearest
+    Div(RoundingMode),
 }
 
 impl fmt::Display for ArithOp {
@@ -303,41 +936,125 @@ impl fmt::Display for ArithOp {
             ArithOp::ImplicitAdd => write!(f, ""),
             ArithOp::Add => write!(f, " + "),
             ArithOp::Sub => write!(f, " - "),
+            ArithOp::Mul => write!(f, " * "),
+            ArithOp::Div(RoundingMode::TowardZero) => write!(f, " / "),
+            ArithOp::Div(RoundingMode::Down) => write!(f, " \\ "),
+            ArithOp::Div(RoundingMode::Up) => write!(f, " \\^ "),
+            ArithOp::Div(RoundingMode::Nearest) => write!(f, " \\~ "),
+        }
+    }
+}
+
+impl ArithOp {
+    /// divide divides `lhs` by `rhs`, rounding by the mode of `Div` (any
+    /// other operator rounds toward zero). Dividing by zero is 0.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::ArithOp;
+    /// use dice_nom::results::RoundingMode;
+    /// let div = |mode| ArithOp::Div(mode);
+    /// assert_eq!(div(RoundingMode::TowardZero).divide(7, 2), 3);
+    /// assert_eq!(div(RoundingMode::TowardZero).divide(-7, 2), -3);
+    /// assert_eq!(div(RoundingMode::Down).divide(7, 2), 3);
+    /// assert_eq!(div(RoundingMode::Down).divide(-7, 2), -4);
+    /// assert_eq!(div(RoundingMode::Up).divide(7, 2), 4);
+    /// assert_eq!(div(RoundingMode::Up).divide(-7, 2), -3);
+    /// assert_eq!(div(RoundingMode::Nearest).divide(7, 2), 4);
+    /// assert_eq!(div(RoundingMode::Nearest).divide(-7, 2), -4);
+    /// assert_eq!(div(RoundingMode::Nearest).divide(7, 3), 2);
+    /// assert_eq!(div(RoundingMode::Up).divide(6, 2), 3);
+    /// assert_eq!(div(RoundingMode::Up).divide(6, 0), 0);
+    /// assert_eq!(div(RoundingMode::Down).divide(7, -2), -4);
+    /// ```
+    pub fn divide(&self, lhs: i32, rhs: i32) -> i32 {
+        if rhs == 0 {
+            return 0;
         }
+        let mode = match self {
+            ArithOp::Div(mode) => *mode,
+            _ => RoundingMode::TowardZero,
+        };
+        // the mode needs a positive divisor
+        let (lhs, rhs) = (lhs as i64 * rhs.signum() as i64, rhs.unsigned_abs() as i64);
+        mode.round(lhs, rhs)
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArithTermGenerator {
     pub op: ArithOp,
     pub term: TermGenerator,
+
+    /// an optional tag for the dice of this term, e.g. `2d6#slashing`
+    pub label: Option<String>,
 }
 
 impl fmt::Display for ArithTermGenerator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}{}", self.op, self.term)
+        write!(f, "{}{}", self.op, self.term)?;
+        if let Some(label) = &self.label {
+            write!(f, "#{}", label)?;
+        }
+        Ok(())
     }
 }
 
 impl ArithTermGenerator {
+    /// generate rolls the term. A subtracted term is rolled in full first,
+    /// operators and all, and then every value in it is negated, so the
+    /// bonus dice of a subtracted explosion (e.g. `- 2d6!`) count against
+    /// the total the same as the dice first rolled.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// // every die is a 6, so the pool explodes once
+    /// let gen = dice_nom::parse("-2d6w[0,0,0,0,0,1]!").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.count(), 4);
+    /// assert_eq!(pool.bonus(), 2);
+    /// assert!(pool.values.iter().all(|v| v.sum() == -6));
+    /// assert_eq!(pool.sum(), -24);
+    ///
+    /// for _ in 0..100 {
+    ///     let pool = dice_nom::parse("-2d6!").unwrap().generate(&mut rng).lhs;
+    ///     assert!(pool.values.iter().all(|v| v.sum() < 0));
+    ///     assert!(pool.values.iter().all(|v| v.is_penalty()));
+    /// }
+    /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
-        let mut pool = self.term.generate(rng);
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
+        let mut pool = self.term.generate_within(rng, budget)?;
         match &self.op {
             ArithOp::Sub => {
                 for idx in 0..pool.count() {
-                    pool.values[idx].mark_penalty();
+                    pool.values[idx].negate();
+                }
+                if pool.has_value() {
+                    pool.set_value(-pool.value());
                 }
-                pool
+                Ok(pool)
             }
-            _ => pool,
+            _ => Ok(pool),
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TermGenerator {
     Pool(PoolGenerator),
     Constant(i32),
+    Decimal(Decimal),
+    Group(Box<ExprGenerator>),
+    Repeat(i32, Box<ExprGenerator>),
 }
 
 impl fmt::Display for TermGenerator {
@@ -345,30 +1062,119 @@ impl fmt::Display for TermGenerator {
         match self {
             TermGenerator::Pool(pg) => write!(f, "{}", pg),
             TermGenerator::Constant(n) => write!(f, "{}", n),
+            TermGenerator::Decimal(n) => write!(f, "{}", n),
+            TermGenerator::Group(expr) => write!(f, "({})", expr),
+            TermGenerator::Repeat(n, expr) => write!(f, "{}x({})", n, expr),
         }
     }
 }
 
 impl TermGenerator {
+    /// generate rolls the term. A repeated expression is rolled that many
+    /// times into one pool whose value is the sum of every roll's value;
+    /// each roll is listed by `Pool::repeats`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("2x(1d1)").unwrap();
+    /// assert_eq!(format!("{}", gen), "2x(1d1)");
+    /// assert_eq!(gen.generate(&mut rng).sum(), 2);
+    ///
+    /// let gen = dice_nom::parse("3x(1d20 + 5)").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// let repeats = pool.repeats();
+    /// assert_eq!(repeats.len(), 3);
+    /// for (values, total) in repeats.iter() {
+    ///     assert_eq!(values.len(), 2);
+    ///     assert_eq!(values[0].value + 5, *total);
+    /// }
+    /// assert_eq!(pool.value(), repeats.iter().map(|(_, total)| total).sum::<i32>());
+    ///
+    /// // each roll keeps its own value
+    /// let gen = dice_nom::parse("2x(1d1 * 3) > 5").unwrap();
+    /// let results = gen.generate(&mut rng);
+    /// assert_eq!(results.lhs.value(), 6);
+    /// assert_eq!(results.sum(), 1);
+    /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn set_rounding(&mut self, mode: RoundingMode) {
         match self {
-            TermGenerator::Pool(pg) => pg.generate(rng),
-            TermGenerator::Constant(n) => Pool::new_with_values(vec![Value::constant(*n)]),
+            TermGenerator::Pool(pg) => pg.set_rounding(mode),
+            TermGenerator::Group(expr) | TermGenerator::Repeat(_, expr) => expr.set_rounding(mode),
+            TermGenerator::Constant(_) | TermGenerator::Decimal(_) => (),
+        }
+    }
+
+    pub(crate) fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
+        match self {
+            TermGenerator::Pool(pg) => pg.generate_within(rng, budget),
+            TermGenerator::Constant(n) => {
+                budget.spend(1)?;
+                Ok(Pool::new_with_values(vec![Value::constant(*n)]))
+            }
+            TermGenerator::Decimal(n) => {
+                budget.spend(1)?;
+                let mut value = Value::constant(n.whole());
+                value.add_fraction(Decimal(n.fraction()));
+                Ok(Pool::new_with_values(vec![value]))
+            }
+            TermGenerator::Group(expr) => expr.generate_within(rng, budget),
+            TermGenerator::Repeat(n, expr) => {
+                let mut pool = Pool::new();
+                let mut total = 0i32;
+                let mut has_value = false;
+                for _ in 0..*n {
+                    let mut roll = expr.generate_within(rng, budget)?;
+                    has_value |= roll.has_value();
+                    total = total.saturating_add(roll.value());
+
+                    let start = pool.count();
+                    let value = roll.value();
+                    pool.append(&mut roll);
+                    pool.push_repeat(start..pool.count(), value);
+                }
+                if has_value {
+                    pool.set_value(total);
+                }
+                Ok(pool)
+            }
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PoolGenerator {
-    pub count: i32,
+    pub count: Count,
     pub range: i32,
-    pub op: Option<PoolOp>,
+    pub die: Die,
+    pub ops: Vec<PoolOp>,
 }
 
 impl fmt::Display for PoolGenerator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}d{}", self.count, self.range)?;
-        if let Some(op) = &self.op {
+        match &self.die {
+            Die::Standard => write!(f, "{}d{}", self.count, self.range)?,
+            Die::Fudge => write!(f, "{}dF", self.count)?,
+            Die::Coin => write!(f, "{}dcoin", self.count)?,
+            Die::D66 => write!(f, "{}d66", self.count)?,
+            Die::Percentile => write!(f, "{}d00", self.count)?,
+            Die::Faces(faces) => {
+                let faces: Vec<String> = faces.iter().map(|n| n.to_string()).collect();
+                write!(f, "{}d[{}]", self.count, faces.join(","))?
+            }
+            Die::Weighted(weights) => {
+                let weights: Vec<String> = weights.iter().map(|n| n.to_string()).collect();
+                write!(f, "{}d{}w[{}]", self.count, self.range, weights.join(","))?
+            }
+            Die::Ranged(low) => write!(f, "{}d[{}-{}]", self.count, low, self.range)?,
+        }
+        for op in self.ops.iter() {
             write!(f, "{}", op)?;
         }
         write!(f, "")
@@ -381,43 +1187,416 @@ impl PoolGenerator {
     /// * Example
     ///
     /// ```
-    /// use dice_nom::generators::{PoolGenerator, PoolOp};
+    /// use dice_nom::generators::{Count, Die, PoolGenerator, PoolOp};
     /// use dice_nom::results::Pool;
     /// use rand::prelude::*;
     /// let mut rng = rand::thread_rng();
-    /// let gen = PoolGenerator{ count: 3, range: 6, op: Some(PoolOp::ExplodeEach(None)) };
+    /// let gen = PoolGenerator{
+    ///     count: Count::Fixed(3),
+    ///     range: 6,
+    ///     die: Die::Standard,
+    ///     ops: vec![PoolOp::ExplodeEach(None)]
+    /// };
     /// let pool = gen.generate(&mut rng);
     /// assert!(pool.count() >= 3);
+    ///
+    /// // the standard presets
+    /// for _ in 0..100 {
+    ///     let coin = dice_nom::parse("1dcoin").unwrap().generate(&mut rng).sum();
+    ///     assert!(coin == 0 || coin == 1);
+    ///
+    ///     let d66 = dice_nom::parse("1d66").unwrap().generate(&mut rng).sum();
+    ///     assert!((1..=6).contains(&(d66 / 10)) && (1..=6).contains(&(d66 % 10)));
+    ///
+    ///     let pool = dice_nom::parse("1d00").unwrap().generate(&mut rng).lhs;
+    ///     let (tens, units) = pool.values[0].percentile_dice().unwrap();
+    ///     assert_eq!(pool.sum(), if tens + units == 0 { 100 } else { tens + units });
+    ///     assert_eq!(format!("{}", pool), format!("{:02}+{} = {}", tens, units, pool.sum()));
+    /// }
+    /// assert_eq!(format!("{}", dice_nom::parse("2d00").unwrap()), "2d00");
+    /// // a repeated `d` is still a single `d`
+    /// assert_eq!(format!("{}", dice_nom::parse("2dd6").unwrap()), "2d6");
+    /// assert!(dice_nom::parse("1d000").is_err());
+    /// assert_eq!(format!("{}", dice_nom::parse("2dcoin").unwrap()), "2dcoin");
+    /// assert_eq!(format!("{}", dice_nom::parse("1dfudge").unwrap()), "1dF");
+    /// assert_eq!(format!("{}", dice_nom::parse("1d660").unwrap()), "1d660");
+    ///
+    /// // a loaded die for tests that always rolls a 6
+    /// let gen = PoolGenerator{
+    ///     count: Count::Fixed(3),
+    ///     range: 6,
+    ///     die: Die::Weighted(vec![0, 0, 0, 0, 0, 1]),
+    ///     ops: vec![]
+    /// };
+    /// assert_eq!(gen.generate(&mut rng).sum(), 18);
+    /// assert_eq!(format!("{}", gen), "3d6w[0,0,0,0,0,1]");
+    /// let gen = dice_nom::parse("3d6w[0,0,0,0,0,1]").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).sum(), 18);
+    /// assert_eq!(gen.expected_value(), Some(18.0));
+    /// assert!(dice_nom::parse("3d6w[0,0,0,0,0,99999999999]").is_err());
+    ///
+    /// // each die is rolled 3 times and the best 2 rolls are summed
+    /// use rand::rngs::StdRng;
+    /// let mut rng = StdRng::seed_from_u64(3);
+    /// let mut check = rng.clone();
+    /// let gen = PoolGenerator{
+    ///     count: Count::Fixed(4),
+    ///     range: 6,
+    ///     die: Die::Standard,
+    ///     ops: vec![PoolOp::BestOfRerolls(3, 2)]
+    /// };
+    /// let pool = gen.generate(&mut rng);
+    /// assert_eq!(pool.count(), 4);
+    /// for v in pool.values.iter() {
+    ///     let mut rolls: Vec<i32> = (0..3).map(|_| check.gen_range(0..6) + 1).collect();
+    ///     rolls.sort_unstable_by(|a, b| b.cmp(a));
+    ///     assert_eq!(v.value, rolls[0]);
+    ///     assert_eq!(v.sum(), rolls[0] + rolls[1]);
+    ///     assert!(v.value <= v.range);
+    /// }
+    ///
+    /// // a die is rolled at most `MAX_ITERATIONS` times
+    /// let gen = PoolGenerator{
+    ///     count: Count::Fixed(1),
+    ///     range: 2_000_000_000,
+    ///     die: Die::Standard,
+    ///     ops: vec![PoolOp::BestOfRerolls(50_000_000, 3)]
+    /// };
+    /// let pool = gen.generate(&mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// assert!(pool.sum() > 0);
     /// ```
     pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Pool {
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    fn set_rounding(&mut self, mode: RoundingMode) {
+        if let Count::Rolled(term) = &mut self.count {
+            term.set_rounding(mode);
+        }
+        for op in self.ops.iter_mut() {
+            op.set_rounding(mode);
+        }
+    }
+
+    pub(crate) fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<Pool, GenError> {
         let mut pool = Pool::new();
-        for _ in 0..self.count {
-            let val = Value::random(self.range, false, rng);
+        for _ in 0..self.count.generate_within(rng, budget)? {
+            let before = pool.count();
+            let val = self.die.roll(self.range, false, rng);
             pool.values.push(val);
-            if let Some(op) = &self.op {
-                op.apply_last(&mut pool, rng);
+            // explosions and rerolls stop just past the budget so that it
+            // is caught; rerolls count against it as any other die
+            let mut rolled = 0;
+            for (idx, op) in self.ops.iter().enumerate() {
+                let most = budget.remaining().saturating_sub(rolled).saturating_add(1);
+                op.apply_last_bounded(&mut pool, &self.die, rng, most, &mut rolled, self.cap_after(idx));
             }
+            budget.spend(pool.count() - before + rolled)?;
         }
 
-        if let Some(op) = &self.op {
-            op.apply_all(&mut pool, rng);
+        let before = pool.count();
+        for (idx, op) in self.ops.iter().enumerate() {
+            let most = (budget.remaining() / pool.count().max(1)).saturating_add(1);
+            op.apply_all_bounded(&mut pool, &self.die, rng, most, self.cap_after(idx));
         }
+        budget.spend(pool.count().saturating_sub(before))?;
 
-        pool
+        Ok(pool)
+    }
+
+    /// cap_after is the total of the first `cap` operator after the
+    /// operator at `idx`; the explosions of that operator stop there.
+    fn cap_after(&self, idx: usize) -> Option<i32> {
+        self.ops[idx + 1..].iter().find_map(|op| match op {
+            PoolOp::CapTotal(cap) => Some(*cap),
+            _ => None,
+        })
+    }
+
+    /// roll is `generate` using the thread local random number generator.
+    ///
+    /// * Example
+    ///
+    /// ```
+    /// let pool = dice_nom::roller(3, 6, None).roll();
+    /// assert_eq!(pool.count(), 3);
+    /// ```
+    pub fn roll(&self) -> Pool {
+        self.generate(&mut rand::thread_rng())
+    }
+}
+
+/// stream_seed mixes the seed, roll and term of `generate_streams` into
+/// the seed of a single term (the SplitMix64 finalizer, so nearby numbers
+/// give unrelated seeds).
+fn stream_seed(seed: u64, roll: u64, term: u64) -> u64 {
+    let mix = |z: u64| {
+        let z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    mix(mix(mix(seed) ^ roll) ^ term)
+}
+
+/// MAX_ROLLED_COUNT is the most dice a pool rolls when its count is itself
+/// rolled; the rolled count is clamped to it, e.g. `(1d1000000)d6`. A
+/// written count is not limited, so use `Generator::generate_bounded` to
+/// limit every die rolled for untrusted expressions.
+pub const MAX_ROLLED_COUNT: i32 = 1000;
+
+/// dice iterates over the rolled (non-constant) values in the pool.
+fn dice(pool: &Pool) -> impl Iterator<Item = &Value> {
+    pool.values.iter().filter(|v| v.is_random())
+}
+
+/// kept_count counts the rolled values in the pool not yet discarded
+fn kept_count(pool: &Pool) -> usize {
+    dice(pool).filter(|v| !v.is_discarded()).count()
+}
+
+/// dice_idxs lists the index of each rolled (non-constant) value in the
+/// pool, in order.
+fn dice_idxs(pool: &Pool) -> Vec<usize> {
+    (0..pool.count()).filter(|&idx| pool.values[idx].is_random()).collect()
+}
+
+/// sort_dice_by_key sorts the rolled values in the pool (a stable sort)
+/// while the constants stay where they are.
+fn sort_dice_by_key<K: Ord>(pool: &mut Pool, key: impl Fn(&Value) -> K) {
+    let idxs = dice_idxs(pool);
+    let mut sorted: Vec<Value> = idxs.iter().map(|&idx| pool.values[idx]).collect();
+    sorted.sort_by_key(|v| key(v));
+    for (idx, v) in idxs.into_iter().zip(sorted) {
+        pool.values[idx] = v;
+    }
+}
+
+/// random_ranges lists the range of each rolled (non-constant) value in
+/// the pool, in order.
+fn random_ranges(pool: &Pool) -> Vec<i32> {
+    dice(pool).map(|v| v.range).collect()
+}
+
+/// Count is the number of dice in a pool: either a fixed number or an
+/// expression that is rolled first, e.g. `(1d4)d6`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Count {
+    Fixed(i32),
+    Rolled(Box<TermGenerator>),
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Count::Fixed(n) => write!(f, "{}", n),
+            Count::Rolled(term) => write!(f, "{}", term),
+        }
+    }
+}
+
+impl Count {
+    /// generate returns the number of dice to roll. A rolled count is
+    /// clamped between 0 and `MAX_ROLLED_COUNT`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::{Count, MAX_ROLLED_COUNT};
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("(1d4)d6").unwrap();
+    /// assert_eq!(format!("{}", gen), "(1d4)d6");
+    /// for _ in 0..20 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert!((1..=4).contains(&pool.count()));
+    ///     assert_eq!(pool.range(), 6);
+    /// }
+    ///
+    /// let gen = dice_nom::parse("(2d1000000 + 1000)d6").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.count(), MAX_ROLLED_COUNT as usize);
+    ///
+    /// let gen = dice_nom::parse("(1d4 - 10)d6").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.count(), 0);
+    ///
+    /// assert_eq!(Count::Fixed(3).generate(&mut rng), 3);
+    /// ```
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        unbounded(self.generate_within(rng, &mut Budget::unbounded()))
+    }
+
+    pub(crate) fn generate_within<R: Rng + ?Sized>(&self, rng: &mut R, budget: &mut Budget) -> Result<i32, GenError> {
+        match self {
+            Count::Fixed(n) => Ok(*n),
+            Count::Rolled(term) => Ok(term.generate_within(rng, budget)?.value().clamp(0, MAX_ROLLED_COUNT)),
+        }
+    }
+
+    /// fixed is the count when it is not rolled.
+    pub fn fixed(&self) -> Option<i32> {
+        match self {
+            Count::Fixed(n) => Some(*n),
+            Count::Rolled(_) => None,
+        }
+    }
+}
+
+/// MAX_WEIGHT is the largest total weight of a weighted die that the
+/// statistics will list face by face.
+const MAX_WEIGHT: u64 = 10_000;
+
+/// Die describes the faces of the dice in a pool. The `range` of the pool
+/// is always the highest face.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Die {
+    /// faces numbered from 1 to the range
+    Standard,
+
+    /// Fudge/Fate dice with the faces -1, 0 and 1
+    Fudge,
+
+    /// a coin with the faces 0 and 1
+    Coin,
+
+    /// two d6 read as tens and units for the values 11 to 66
+    D66,
+
+    /// a tens die and a units die for the values 1 to 100, where 00 and 0
+    /// are 100, e.g. `d00`
+    Percentile,
+
+    /// an explicit list of faces, e.g. `[0,0,1,1,2,3]`; faces may repeat
+    Faces(Vec<i32>),
+
+    /// faces numbered from 1 to the range, each as likely as its weight,
+    /// e.g. `d6w[0,0,0,0,0,1]` always rolls a 6
+    Weighted(Vec<u32>),
+
+    /// faces numbered from a low face to the range, e.g. `d[0-9]`
+    Ranged(i32),
+}
+
+impl Die {
+    /// roll generates a single value from the faces of this die.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::Die;
+    /// let mut rng = rand::thread_rng();
+    /// let val = Die::Standard.roll(6, false, &mut rng);
+    /// assert!(val.value >= 1 && val.value <= 6);
+    ///
+    /// let val = Die::Fudge.roll(1, false, &mut rng);
+    /// assert!(val.value >= -1 && val.value <= 1);
+    /// assert!(val.is_fudge());
+    ///
+    /// let val = Die::D66.roll(66, false, &mut rng);
+    /// assert!((1..=6).contains(&(val.value / 10)) && (1..=6).contains(&(val.value % 10)));
+    ///
+    /// let val = Die::Percentile.roll(100, false, &mut rng);
+    /// assert!(val.value >= 1 && val.value <= 100);
+    /// assert!(val.percentile_dice().is_some());
+    ///
+    /// let val = Die::Faces(vec![0, 0, 1, 1, 2, 3]).roll(3, false, &mut rng);
+    /// assert!(val.value >= 0 && val.value <= 3);
+    ///
+    /// let val = Die::Ranged(0).roll(9, false, &mut rng);
+    /// assert!(val.value >= 0 && val.value <= 9);
+    /// assert_eq!(val.range, 9);
+    /// ```
+    pub fn roll<R: Rng + ?Sized>(&self, range: i32, bonus: bool, rng: &mut R) -> Value {
+        match self {
+            Die::Standard => Value::random(range, bonus, rng),
+            Die::Fudge => Value::fudge(bonus, rng),
+            Die::Coin => Value::random_with_value(rng.gen_range(0..=1), range, bonus),
+            Die::D66 => {
+                let n = (rng.gen_range(0..6) + 1) * 10 + rng.gen_range(0..6) + 1;
+                Value::random_with_value(n, range, bonus)
+            }
+            Die::Percentile => Value::percentile(bonus, rng),
+            Die::Faces(faces) => match faces.choose(rng) {
+                Some(&n) => Value::random_with_value(n, range, bonus),
+                None => Value::random(range, bonus, rng),
+            },
+            Die::Weighted(weights) => Value::weighted(range, weights, bonus, rng),
+            Die::Ranged(low) => Value::random_from(*low, range, bonus, rng),
+        }
+    }
+
+    /// faces lists every face of this die, each equally likely.
+    pub fn faces(&self, range: i32) -> Vec<i32> {
+        match self {
+            Die::Standard | Die::Percentile => (1..=range).collect(),
+            Die::Fudge => vec![-1, 0, 1],
+            Die::Coin => vec![0, 1],
+            Die::D66 => (1..=6).flat_map(|t| (1..=6).map(move |u| t * 10 + u)).collect(),
+            Die::Faces(faces) => faces.clone(),
+            Die::Ranged(low) => (*low..=range).collect(),
+            Die::Weighted(weights) => {
+                // each face is listed as often as its weight; large weights
+                // are left out rather than listing millions of faces
+                let weights = &weights[..weights.len().min(range.max(0) as usize)];
+                let total: u64 = weights.iter().map(|&w| w as u64).sum();
+                if total == 0 {
+                    (1..=range).collect()
+                } else if total > MAX_WEIGHT {
+                    vec![]
+                } else {
+                    (1..)
+                        .zip(weights)
+                        .flat_map(|(n, &w)| std::iter::repeat_n(n, w as usize))
+                        .collect()
+                }
+            }
+        }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PoolOp {
     Explode(Option<i32>),
     ExplodeUntil(Option<i32>),
     ExplodeEach(Option<i32>),
     ExplodeEachUntil(Option<i32>),
+    ExplodeOn(Vec<i32>),
+    ExplosionLimit(Box<PoolOp>, i32),
+    ExplodeTop(Box<PoolOp>, i32),
+    ExplodeRolled(Box<ExprGenerator>),
+    Penetrate(Option<i32>),
+    Compound(Option<i32>),
+    OpenEnded(Option<i32>),
+    BestOfRerolls(i32, i32),
+    RerollBelow(i32),
+    RerollUntilAbove(i32),
+    LuckyHigh,
+    LuckyLow,
+    FloorEach(i32),
     AddEach(Option<i32>),
     SubEach(Option<i32>),
+    AddFraction(Decimal),
     TakeMid(i32),
+    TakeMidLow(i32),
     TakeLow(i32),
     TakeHigh(i32),
+    TakeLowPercent(i32),
+    TakeHighPercent(i32),
+    KeepBestPerGroup(i32, i32),
+    DropLow(i32),
+    DropHigh(i32),
+    KeepUnique(i32),
+    DropValue(i32),
+    KeepValues(Vec<i32>),
+    CountMatches(i32),
+    TakeMin,
+    TakeMax,
+    Critical,
+    Sort(bool),
+    CapTotal(i32),
+    CapEach(i32),
     Disadvantage,
     Advantage,
     BestGroup,
@@ -458,6 +1637,48 @@ impl fmt::Display for PoolOp {
                 }
             }
 
+            PoolOp::ExplodeOn(values) => {
+                let values: Vec<String> = values.iter().map(|n| n.to_string()).collect();
+                write!(f, "!=[{}]", values.join(","))
+            }
+
+            PoolOp::ExplodeRolled(expr) => write!(f, "!({})", expr),
+
+            PoolOp::ExplosionLimit(op, n) => write!(f, "{}:{}", op, n),
+
+            PoolOp::ExplodeTop(op, k) => write!(f, "{}t{}", op, k),
+
+            PoolOp::Penetrate(n) => {
+                if let Some(n) = *n {
+                    write!(f, "p{}", n)
+                } else {
+                    write!(f, "p")
+                }
+            }
+
+            PoolOp::OpenEnded(n) => {
+                if let Some(n) = *n {
+                    write!(f, "o{}", n)
+                } else {
+                    write!(f, "o")
+                }
+            }
+
+            PoolOp::Compound(n) => {
+                if let Some(n) = *n {
+                    write!(f, "!c{}", n)
+                } else {
+                    write!(f, "!c")
+                }
+            }
+
+            PoolOp::BestOfRerolls(rolls, keep) => write!(f, "b{}k{}", rolls, keep),
+            PoolOp::RerollBelow(n) => write!(f, "r{}", n),
+            PoolOp::RerollUntilAbove(n) => write!(f, "rr{}", n),
+            PoolOp::LuckyHigh => write!(f, "L"),
+            PoolOp::LuckyLow => write!(f, "l"),
+            PoolOp::FloorEach(n) => write!(f, "_{}", n),
+
             PoolOp::AddEach(n) => {
                 if let Some(n) = *n {
                     write!(f, "++{}", n)
@@ -474,24 +1695,75 @@ impl fmt::Display for PoolOp {
                 }
             }
 
+            PoolOp::AddFraction(n) => {
+                if n.0 < 0 {
+                    write!(f, "--{}", Decimal(-n.0))
+                } else {
+                    write!(f, "++{}", n)
+                }
+            }
+
             PoolOp::TakeMid(n) => write!(f, "~{}", n),
+            PoolOp::TakeMidLow(n) => write!(f, "~`{}", n),
             PoolOp::TakeLow(n) => write!(f, "`{}", n),
             PoolOp::TakeHigh(n) => write!(f, "^{}", n),
-            PoolOp::Disadvantage => write!(f, " DIS"),
-            PoolOp::Advantage => write!(f, " ADV"),
+            PoolOp::TakeLowPercent(n) => write!(f, "`{}%", n),
+            PoolOp::TakeHighPercent(n) => write!(f, "^{}%", n),
+            PoolOp::KeepBestPerGroup(size, keep) => write!(f, "^^{}/{}", keep, size),
+            PoolOp::DropLow(n) => write!(f, "dl{}", n),
+            PoolOp::DropHigh(n) => write!(f, "dh{}", n),
+            PoolOp::KeepUnique(n) => write!(f, "u{}", n),
+            PoolOp::DropValue(n) => write!(f, "x{}", n),
+            PoolOp::KeepValues(values) => {
+                let values: Vec<String> = values.iter().map(|n| n.to_string()).collect();
+                write!(f, "k[{}]", values.join(","))
+            }
+            PoolOp::CountMatches(n) => write!(f, "=m{}", n),
+            PoolOp::TakeMin => write!(f, "min"),
+            PoolOp::TakeMax => write!(f, "max"),
+            PoolOp::Critical => write!(f, "$"),
+            PoolOp::Sort(true) => write!(f, "s"),
+            PoolOp::Sort(false) => write!(f, "S"),
+            PoolOp::CapTotal(n) => write!(f, " cap {}", n),
+            PoolOp::CapEach(n) => write!(f, "v{}", n),
+            PoolOp::Disadvantage => write!(f, "DIS"),
+            PoolOp::Advantage => write!(f, "ADV"),
             PoolOp::BestGroup => write!(f, "Y"),
         }
     }
 }
 
 impl PoolOp {
+    fn set_rounding(&mut self, mode: RoundingMode) {
+        match self {
+            PoolOp::ExplodeRolled(expr) => expr.set_rounding(mode),
+            PoolOp::ExplosionLimit(op, _) => op.set_rounding(mode),
+            _ => (),
+        }
+    }
+
+    /// with_threshold is the explosion with `n` as its threshold. Only an
+    /// explosion whose optional threshold is not set has one.
+    pub(crate) fn with_threshold(&self, n: i32) -> Option<PoolOp> {
+        match self {
+            PoolOp::Explode(None) => Some(PoolOp::Explode(Some(n))),
+            PoolOp::ExplodeUntil(None) => Some(PoolOp::ExplodeUntil(Some(n))),
+            PoolOp::ExplodeEach(None) => Some(PoolOp::ExplodeEach(Some(n))),
+            PoolOp::ExplodeEachUntil(None) => Some(PoolOp::ExplodeEachUntil(Some(n))),
+            PoolOp::Penetrate(None) => Some(PoolOp::Penetrate(Some(n))),
+            PoolOp::Compound(None) => Some(PoolOp::Compound(Some(n))),
+            PoolOp::OpenEnded(None) => Some(PoolOp::OpenEnded(Some(n))),
+            _ => None,
+        }
+    }
+
     /// apply_last modifies the pool based on the current operator.
     /// Some operators do not act on individual values and are skipped.
     ///
     /// * Examples
     ///
     /// ```
-    /// use dice_nom::generators::PoolOp;
+    /// use dice_nom::generators::{PoolOp, MAX_ITERATIONS};
     /// use dice_nom::results::{ Value, Pool };
     /// use rand::prelude::*;
     /// let mut rng = rand::thread_rng();
@@ -501,6 +1773,7 @@ impl PoolOp {
     /// PoolOp::ExplodeEach(None).apply_last(&mut pool, &mut rng);
     /// assert_eq!(pool.count(), 2); // value is max so it should "explode"
     /// assert_eq!(pool.bonus(), 1); // rerolled value is considered bonus
+    /// assert!(!pool.values[1].is_rerolled()); // but it did not replace a roll
     /// assert_eq!(pool.kept(), 2); // all values are kept
     /// assert!(pool.sum() > 6); // new roll is added to existing roll
     ///
@@ -508,6 +1781,36 @@ impl PoolOp {
     /// PoolOp::ExplodeEachUntil(None).apply_last(&mut pool, &mut rng);
     /// assert!(pool.count() >= 2); // value is max so it should "explode"; may continue to explode
     ///
+    /// // only the listed values explode, however high the others are
+    /// let mut pool = Pool::from_faces(&[(5, 6)]);
+    /// PoolOp::ExplodeOn(vec![6]).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// let mut pool = Pool::from_faces(&[(1, 6)]);
+    /// PoolOp::ExplodeOn(vec![1, 6]).apply_last(&mut pool, &mut rng);
+    /// assert!(pool.count() >= 2);
+    /// assert!(pool.values[..pool.count() - 1].iter().all(|v| v.value == 1 || v.value == 6));
+    /// assert!(pool.values[1..].iter().all(|v| v.is_bonus()));
+    ///
+    /// // penetrating dice subtract 1 from every bonus die
+    /// let mut pool = Pool::new_with_values(vec![val]);
+    /// PoolOp::Penetrate(None).apply_last(&mut pool, &mut rng);
+    /// assert!(pool.count() >= 2);
+    /// assert!(pool.values[1..].iter().all(|v| v.is_bonus() && v.modifier() == -1));
+    /// let rolled: i32 = pool.values.iter().map(|v| v.value).sum();
+    /// assert_eq!(pool.sum(), rolled - (pool.count() as i32 - 1));
+    ///
+    /// // compounding dice add their explosions into the same value
+    /// let mut pool = Pool::new_with_values(vec![val]);
+    /// PoolOp::Compound(None).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// assert!(pool.sum() > 6);
+    /// assert_eq!(format!("{}", pool.values[0]), format!("{}", pool.sum()));
+    ///
+    /// // every roll meets a threshold of 1, so compounding stops at the cap
+    /// let mut pool = Pool::new_with_values(vec![val]);
+    /// PoolOp::Compound(Some(1)).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    ///
     /// let mut pool = Pool::new_with_values(vec![val]);
     /// PoolOp::AddEach(Some(4)).apply_last(&mut pool, &mut rng);
     /// assert_eq!(pool.sum(), 10);
@@ -519,44 +1822,396 @@ impl PoolOp {
     /// assert_eq!(pool.sum(), 2);
     /// assert_eq!(pool.values[0].modifier(), -4);
     /// assert_eq!(pool.values[0].sum(), 2);
+    ///
+    /// // a later modifier replaces an earlier one
+    /// let mut pool = Pool::new_with_values(vec![val]);
+    /// PoolOp::AddEach(Some(1)).apply_last(&mut pool, &mut rng);
+    /// PoolOp::AddEach(Some(2)).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.values[0].modifier(), 2);
+    /// assert_eq!(pool.sum(), 8);
+    ///
+    /// // but it is added on top of compounded rolls and penalties
+    /// let gen = dice_nom::parse("1d6w[0,0,0,0,0,1]!c:2++2").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).sum(), 20);
+    /// let gen = dice_nom::parse("1d6w[0,0,0,0,0,1]p:1++2").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.values[1].sum(), 7);
+    /// assert_eq!(pool.sum(), 13);
+    /// let gen = dice_nom::parse("1d6w[0,0,0,0,0,1]b3k2++1").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).sum(), 13);
+    ///
+    /// // floors compose with the add operator: floor first, then add
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(1, 6, false)]);
+    /// PoolOp::FloorEach(3).apply_last(&mut pool, &mut rng);
+    /// PoolOp::AddEach(Some(2)).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.values[0].value, 1);
+    /// assert_eq!(pool.sum(), 5);
+    ///
+    /// // the threshold is rolled again for every check and clamped to the
+    /// // die range, so a 4 only explodes when the threshold rolls low enough
+    /// use dice_nom::parsers::expr_parser;
+    /// use rand::rngs::StdRng;
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let (_, threshold) = expr_parser("1d6").unwrap();
+    /// let op = PoolOp::ExplodeRolled(Box::new(threshold));
+    /// let mut counts = std::collections::BTreeSet::new();
+    /// for _ in 0..100 {
+    ///     let val = Value::random_with_value(4, 6, false);
+    ///     let mut pool = Pool::new_with_values(vec![val]);
+    ///     op.apply_last(&mut pool, &mut rng);
+    ///     counts.insert(pool.count());
+    /// }
+    /// assert!(counts.contains(&1));
+    /// assert!(counts.len() > 1);
+    ///
+    /// // a die at or below the threshold is replaced by a single reroll
+    /// let mut check = rng.clone();
+    /// let mut pool = Pool::new_with_values(vec![val]);
+    /// PoolOp::RerollBelow(6).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// assert_eq!(pool.values[0].value, check.gen_range(0..6) + 1);
+    /// assert_eq!(pool.sum(), pool.values[0].value);
+    /// assert!(pool.values[0].is_rerolled());
+    /// assert!(format!("{}", pool).contains('~'));
+    ///
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(3, 6, false)]);
+    /// PoolOp::RerollBelow(2).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.values[0].value, 3);
+    /// assert!(!pool.values[0].is_rerolled());
+    ///
+    /// // rerolled until above the threshold; a threshold at or above the
+    /// // range can never be beaten so the die is left alone
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(1, 6, false)]);
+    /// PoolOp::RerollUntilAbove(4).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// assert!(pool.values[0].value > 4);
+    ///
+    /// let gen = dice_nom::parse("d6rr6").unwrap();
+    /// assert!(gen.generate(&mut rng).sum() <= 6);
+    ///
+    /// // each die is rolled twice and the better (or worse) roll is kept
+    /// let gen = dice_nom::parse("d20L").unwrap();
+    /// assert_eq!(format!("{}", gen), "1d20L");
+    /// for _ in 0..100 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert_eq!(pool.count(), 2);
+    ///     assert_eq!(pool.kept(), 1);
+    ///     let high = pool.values.iter().map(|v| v.value).max().unwrap();
+    ///     assert_eq!(pool.sum(), high);
+    /// }
+    ///
+    /// // open-ended dice explode up on the highest face and down (as
+    /// // negative bonus dice) on the lowest
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(1, 10, false)]);
+    /// PoolOp::OpenEnded(None).apply_last(&mut pool, &mut rng);
+    /// assert!(pool.count() >= 2);
+    /// assert!(pool.values[1..].iter().all(|v| v.is_bonus() && v.is_penalty()));
+    /// assert_eq!(pool.sum(), 1 - pool.values[1..].iter().map(|v| v.value).sum::<i32>());
+    ///
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(97, 100, false)]);
+    /// PoolOp::OpenEnded(Some(96)).apply_last(&mut pool, &mut rng);
+    /// assert!(pool.count() >= 2);
+    /// assert_eq!(pool.sum(), pool.values.iter().map(|v| v.value).sum::<i32>());
+    ///
+    /// let mut down = Value::random_with_value(42, 100, true);
+    /// down.mark_penalty();
+    /// let pool = Pool::new_with_values(vec![Value::random_with_value(3, 100, false), down]);
+    /// assert_eq!(format!("{}", pool), "3, -42* = -39");
+    ///
+    /// let gen = dice_nom::parse("d1o").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.count(), MAX_ITERATIONS + 1);
+    ///
+    /// let gen = dice_nom::parse("2d20l").unwrap();
+    /// assert_eq!(format!("{}", gen), "2d20l");
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.count(), 4);
+    /// for pair in pool.values.chunks(2) {
+    ///     let low = pair[0].value.min(pair[1].value);
+    ///     assert_eq!(pair.iter().filter(|v| !v.is_discarded()).map(|v| v.value).sum::<i32>(), low);
+    /// }
     /// ```
     pub fn apply_last<R: Rng + ?Sized>(&self, pool: &mut Pool, rng: &mut R) {
-        if pool.count() == 0 {
-            return;
+        self.apply_last_with(pool, &Die::Standard, rng)
+    }
+
+    /// apply_last_with is `apply_last` for pools of any kind of die; any
+    /// new dice (explosions, rerolls) are rolled from the faces of `die`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::{Die, PoolOp};
+    /// use dice_nom::results::{Pool, Value};
+    /// let mut rng = rand::thread_rng();
+    /// let die = Die::Faces(vec![0, 0, 3]);
+    /// let mut pool = Pool::new_with_values(vec![Value::random_with_value(3, 3, false)]);
+    /// PoolOp::ExplodeEach(None).apply_last_with(&mut pool, &die, &mut rng);
+    /// assert_eq!(pool.count(), 2);
+    /// assert!(pool.values[1].value == 0 || pool.values[1].value == 3);
+    /// ```
+    pub fn apply_last_with<R: Rng + ?Sized>(&self, pool: &mut Pool, die: &Die, rng: &mut R) {
+        self.apply_last_bounded(pool, die, rng, MAX_ITERATIONS, &mut 0, None)
+    }
+
+    /// apply_last_bounded is `apply_last_with` where no die explodes or is
+    /// rerolled more than `most` times, whatever its own limit. The dice
+    /// rolled that are not added to the pool (rerolls, compounded rolls and
+    /// rolled thresholds) are added to `rolled`. Explosions stop once the
+    /// pool sum would pass `cap`.
+    fn apply_last_bounded<R: Rng + ?Sized>(
+        &self,
+        pool: &mut Pool,
+        die: &Die,
+        rng: &mut R,
+        most: usize,
+        rolled: &mut usize,
+        cap: Option<i32>,
+    ) {
+        match self {
+            PoolOp::ExplosionLimit(op, n) => {
+                op.apply_last_limited(pool, die, rng, explosion_limit(*n).min(most), rolled, cap)
+            }
+            _ => self.apply_last_limited(pool, die, rng, MAX_ITERATIONS.min(most), rolled, cap),
+        }
+    }
+
+    /// apply_last_limited is `apply_last_with` where each die explodes or
+    /// is rerolled at most `limit` times.
+    fn apply_last_limited<R: Rng + ?Sized>(
+        &self,
+        pool: &mut Pool,
+        die: &Die,
+        rng: &mut R,
+        limit: usize,
+        rolled: &mut usize,
+        cap: Option<i32>,
+    ) {
+        match pool.values.last() {
+            Some(last) if last.is_random() => (),
+            _ => return,
         }
 
         match self {
+            PoolOp::ExplodeTop(op, k) => {
+                // the top `k` faces of the die that was just rolled
+                let range = pool.values.last().unwrap().range;
+                if let Some(op) = op.with_threshold(range.saturating_sub(*k).saturating_add(1)) {
+                    op.apply_last_limited(pool, die, rng, limit, rolled, cap);
+                }
+            }
+
             PoolOp::ExplodeEach(n) => {
                 let last = *pool.values.last().unwrap();
                 let n = n.unwrap_or(last.range);
-                if last.value >= n {
-                    let new_roll = Value::random(last.range, true, rng);
-                    pool.values.push(new_roll);
+                if limit > 0 && last.value >= n {
+                    let new_roll = die.roll(last.range, true, rng);
+                    push_bonus(pool, new_roll, cap);
                 }
             }
 
-            PoolOp::ExplodeEachUntil(n) => loop {
-                let last = *pool.values.last().unwrap();
-                let n = n.unwrap_or(last.range);
-                if last.value >= n {
-                    let new_roll = Value::random(last.range, true, rng);
-                    pool.values.push(new_roll);
+            PoolOp::ExplodeEachUntil(n) => {
+                for _ in 0..limit {
+                    let last = *pool.values.last().unwrap();
+                    let n = n.unwrap_or(last.range);
+                    if last.value >= n {
+                        let new_roll = die.roll(last.range, true, rng);
+                        if !push_bonus(pool, new_roll, cap) {
+                            break;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            PoolOp::ExplodeOn(values) => {
+                for _ in 0..limit {
+                    let last = *pool.values.last().unwrap();
+                    if values.contains(&last.value) {
+                        let new_roll = die.roll(last.range, true, rng);
+                        if !push_bonus(pool, new_roll, cap) {
+                            break;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            PoolOp::ExplodeRolled(expr) => {
+                for _ in 0..limit {
+                    let last = *pool.values.last().unwrap();
+                    if last.range < 2 {
+                        break;
+                    }
+
+                    let threshold = expr.generate(rng);
+                    *rolled += threshold.count();
+                    let n = threshold.sum().clamp(2, last.range);
+                    if last.value >= n {
+                        let new_roll = die.roll(last.range, true, rng);
+                        if !push_bonus(pool, new_roll, cap) {
+                            break;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            PoolOp::Penetrate(n) => {
+                for _ in 0..limit {
+                    let last = *pool.values.last().unwrap();
+                    let n = n.unwrap_or(last.range);
+                    if last.value >= n {
+                        let mut new_roll = die.roll(last.range, true, rng);
+                        new_roll.set_modifier(-1);
+                        if !push_bonus(pool, new_roll, cap) {
+                            break;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            PoolOp::OpenEnded(n) => {
+                // only the die first rolled can open the chain; the same
+                // number of faces at the bottom explode down
+                let first = *pool.values.last().unwrap();
+                if first.is_bonus() {
+                    return;
+                }
+
+                let high = n.unwrap_or(first.range);
+                let low = first.range - high + 1;
+                let penalty = if first.value >= high {
+                    false
+                } else if first.value <= low {
+                    true
                 } else {
-                    break;
+                    return;
+                };
+
+                for _ in 0..limit {
+                    let mut roll = die.roll(first.range, true, rng);
+                    if penalty {
+                        roll.mark_penalty();
+                    }
+                    if !push_bonus(pool, roll, cap) || roll.value < high {
+                        break;
+                    }
                 }
-            },
+            }
+
+            PoolOp::Compound(n) => {
+                let mut last = pool.values.pop().unwrap();
+                let n = n.unwrap_or(last.range);
+                let mut roll = last.value;
+                for _ in 0..limit {
+                    if roll < n {
+                        break;
+                    }
+
+                    roll = die.roll(last.range, false, rng).value;
+                    *rolled += 1;
+                    last.set_modifier(last.modifier() + roll);
+                }
+                pool.values.push(last);
+            }
+
+            PoolOp::BestOfRerolls(rolls, keep) => {
+                if *rolls < 2 {
+                    return;
+                }
+
+                // the best roll is the face of the die and the other kept
+                // rolls are added to it, as a compounded die is
+                let last = pool.values.pop().unwrap();
+                let rolls = (*rolls as usize).min(limit);
+                let mut values = vec![last.value];
+                for _ in 1..rolls {
+                    values.push(die.roll(last.range, false, rng).value);
+                }
+                *rolled += values.len() - 1;
+                values.sort_unstable_by(|a, b| b.cmp(a));
+                let rest = values[1..]
+                    .iter()
+                    .take(((*keep).max(1) - 1) as usize)
+                    .fold(0i32, |sum, &v| sum.saturating_add(v));
+                let mut best = Value::random_with_value(values[0], last.range, last.is_bonus());
+                best.set_modifier(rest);
+                best.mark_rerolled();
+                pool.values.push(best);
+            }
+
+            PoolOp::RerollBelow(n) => {
+                let last = *pool.values.last().unwrap();
+                if last.value <= *n {
+                    let mut roll = die.roll(last.range, last.is_bonus(), rng);
+                    *rolled += 1;
+                    roll.mark_rerolled();
+                    pool.values.pop();
+                    pool.values.push(roll);
+                }
+            }
+
+            PoolOp::RerollUntilAbove(n) => {
+                let mut last = *pool.values.last().unwrap();
+                if *n >= last.range {
+                    return;
+                }
+
+                for _ in 0..limit {
+                    if last.value > *n {
+                        break;
+                    }
+
+                    last = die.roll(last.range, last.is_bonus(), rng);
+                    *rolled += 1;
+                    last.mark_rerolled();
+                    pool.values.pop();
+                    pool.values.push(last);
+                }
+            }
+
+            PoolOp::LuckyHigh | PoolOp::LuckyLow => {
+                // both rolls are listed; ties keep the first roll
+                let first = *pool.values.last().unwrap();
+                let mut second = die.roll(first.range, first.is_bonus(), rng);
+                second.mark_rerolled();
+                pool.values.push(second);
+
+                let keep_second = match self {
+                    PoolOp::LuckyHigh => second.value > first.value,
+                    _ => second.value < first.value,
+                };
+                let last = pool.count() - 1;
+                let loser = if keep_second { last - 1 } else { last };
+                pool.values[loser].mark_discarded();
+            }
+
+            PoolOp::FloorEach(n) => {
+                let mut last = pool.values.pop().unwrap();
+                last.set_floor(*n);
+                pool.values.push(last);
+            }
 
             PoolOp::AddEach(n) => {
                 let mut last = pool.values.pop().unwrap();
-                let n = n.unwrap_or(1);
-                last.set_modifier(n);
+                last.set_each(n.unwrap_or(1));
                 pool.values.push(last);
             }
 
             PoolOp::SubEach(n) => {
                 let mut last = pool.values.pop().unwrap();
-                let n = -n.unwrap_or(1);
-                last.set_modifier(n);
+                last.set_each(-n.unwrap_or(1));
+                pool.values.push(last);
+            }
+
+            PoolOp::AddFraction(n) => {
+                let mut last = pool.values.pop().unwrap();
+                last.set_each_fraction(*n);
                 pool.values.push(last);
             }
             _ => (),
@@ -567,12 +2222,17 @@ impl PoolOp {
     /// that may modify the entire dice pool. Some operators only apply to
     /// individual values and are ignored here.
     ///
+    /// `TakeLow`, `TakeHigh`, `DropLow` and `DropHigh` (and the percent
+    /// takes) never empty a pool of dice: at least one die that was kept
+    /// before stays kept, so `4d6dl5` keeps the best die.
+    ///
     /// * Examples
     ///
     /// ```
-    /// use dice_nom::generators::PoolOp;
+    /// use dice_nom::generators::{PoolOp, TargetOp};
     /// use dice_nom::results::{ Value, Pool };
     /// use rand::prelude::*;
+    /// use rand::rngs::StdRng;
     /// let mut rng = rand::thread_rng();
     /// let val1 = Value::random_with_value(6, 6, false);
     /// let val2 = Value::random_with_value(5, 6, false);
@@ -615,6 +2275,238 @@ impl PoolOp {
     /// assert_eq!(pool.kept(), 2);
     /// assert_eq!(pool.sum(), 11);
     ///
+    /// // with an odd number of dice to discard, `~` keeps the higher middle
+    /// // dice and `~`` the lower
+    /// let mut pool = Pool::from_faces(&[(3, 6), (6, 6), (1, 6), (5, 6), (4, 6)]);
+    /// PoolOp::TakeMid(2).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(format!("{:#}", pool), "5, 4 = 9");
+    /// let mut pool = Pool::from_faces(&[(3, 6), (6, 6), (1, 6), (5, 6), (4, 6)]);
+    /// PoolOp::TakeMidLow(2).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(format!("{:#}", pool), "4, 3 = 7");
+    ///
+    /// // the middle is taken from the dice an earlier operator kept
+    /// let mut pool = Pool::from_faces(&[(5, 6), (3, 6), (2, 6), (2, 6)]);
+    /// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rng);
+    /// PoolOp::TakeMid(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(pool.sum(), 5);
+    /// let mut pool = Pool::from_faces(&[(5, 6), (3, 6), (2, 6), (2, 6)]);
+    /// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rng);
+    /// PoolOp::TakeMidLow(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.sum(), 3);
+    ///
+    /// for expr in ["4d6^2~1", "4d6^2~`1", "4d6^1~1"] {
+    ///     let gen = dice_nom::parse(expr).unwrap();
+    ///     for _ in 0..20 {
+    ///         let pool = gen.generate(&mut rng).lhs;
+    ///         assert_eq!(pool.kept(), 1);
+    ///         assert!(pool.sum() > 0);
+    ///     }
+    /// }
+    ///
+    /// let gen = dice_nom::parse("5d6~`2").unwrap();
+    /// assert_eq!(format!("{}", gen), "5d6~`2");
+    /// for _ in 0..100 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     let mut values: Vec<i32> = pool.values.iter().map(|v| v.value).collect();
+    ///     values.sort_unstable();
+    ///     assert_eq!(pool.sum(), values[1] + values[2]);
+    /// }
+    ///
+    /// // percentages are of the dice still kept, including any bonus dice
+    /// let mut pool = Pool::new_with_values(vec![val1, val2, val3, val4, val5]);
+    /// PoolOp::TakeHighPercent(50).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 3);
+    /// assert_eq!(pool.sum(), 17);
+    ///
+    /// let gen = dice_nom::parse("10d6 kh50%").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.kept(), 5);
+    ///
+    /// let gen = dice_nom::parse("10d6^6^50%").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.kept(), 3);
+    /// assert_eq!(dice_nom::parse("4d6^2^50%").unwrap().max_possible(), Some(6));
+    ///
+    /// // a percentage over 100 keeps every die
+    /// let gen = dice_nom::parse("4d6^999999999%").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.kept(), 4);
+    ///
+    /// // dropping counts the dice removed rather than the dice kept
+    /// let mut pool = Pool::new_with_values(vec![val1, val2, val3, val4, val5]);
+    /// PoolOp::DropLow(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 4);
+    /// assert_eq!(pool.sum(), 18);
+    ///
+    /// let mut pool = Pool::new_with_values(vec![val1, val2, val3, val4]);
+    /// PoolOp::DropHigh(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 3);
+    /// assert_eq!(pool.sum(), 12);
+    ///
+    /// // take and drop always keep at least one die: dropping more dice
+    /// // than were rolled keeps the best one, and the die kept is one that
+    /// // was kept before
+    /// let mut pool = Pool::new_with_values(vec![val1, val2]);
+    /// PoolOp::DropLow(3).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(pool.sum(), 6);
+    ///
+    /// let gen = dice_nom::parse("4d6dl5").unwrap();
+    /// for _ in 0..20 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert_eq!(pool.kept(), 1);
+    ///     assert!(pool.sum() > 0);
+    ///     assert_eq!(pool.sum(), pool.values.iter().map(|v| v.value).max().unwrap());
+    /// }
+    ///
+    /// let mut pool = Pool::new_with_values(vec![val1, val2, val3, val4]);
+    /// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rng);
+    /// PoolOp::DropHigh(5).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(pool.sum(), 6);
+    /// PoolOp::TakeLow(0).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 1);
+    ///
+    /// // a drop after a take drops from the dice still kept
+    /// let mut pool = Pool::from_faces(&[(6, 6), (5, 6), (4, 6), (2, 6)]);
+    /// PoolOp::TakeHigh(3).apply_all(&mut pool, &mut rng);
+    /// PoolOp::DropLow(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert_eq!(pool.sum(), 11);
+    ///
+    /// let gen = dice_nom::parse("4d6^3dl1").unwrap();
+    /// for _ in 0..20 {
+    ///     assert_eq!(gen.generate(&mut rng).lhs.kept(), 2);
+    /// }
+    /// assert_eq!(gen.max_possible(), Some(12));
+    /// let gen = dice_nom::parse("4d6^3dh1").unwrap();
+    /// assert_eq!(gen.generate(&mut rng).lhs.kept(), 2);
+    ///
+    /// // the best of each pair, in the order rolled
+    /// let mut pool = Pool::from_faces(&[(3, 20), (17, 20), (12, 20), (4, 20)]);
+    /// PoolOp::KeepBestPerGroup(2, 1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(format!("{}", pool), "3-, 17, 12, 4- = 29");
+    ///
+    /// let vals = [3, 3, 5, 1, 5].iter().map(|&v| Value::random_with_value(v, 6, false)).collect();
+    /// let mut pool = Pool::new_with_values(vals);
+    /// PoolOp::KeepUnique(2).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 5);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert!(!pool.values[0].is_discarded());
+    /// assert!(!pool.values[2].is_discarded());
+    /// assert_eq!(pool.sum(), 8);
+    ///
+    /// let mut pool = Pool::from_faces(&[(1, 6), (4, 6), (1, 6)]);
+    /// PoolOp::DropValue(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 3);
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(format!("{}", pool), "1-, 4, 1- = 4");
+    ///
+    /// // every die can be dropped
+    /// let mut pool = Pool::from_faces(&[(1, 6), (1, 6)]);
+    /// PoolOp::DropValue(1).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 0);
+    /// assert_eq!(pool.sum(), 0);
+    ///
+    /// // only the listed values are kept, so only they are summed or hit
+    /// let mut pool = Pool::from_faces(&[(6, 6), (2, 6), (1, 6), (6, 6), (4, 6)]);
+    /// PoolOp::KeepValues(vec![6]).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert_eq!(pool.sum(), 12);
+    /// assert_eq!(format!("{}", pool), "6, 2-, 1-, 6, 4- = 12");
+    /// TargetOp::TargetHigh(4).apply(&mut pool);
+    /// assert_eq!(pool.hits(), 2);
+    ///
+    /// // the number of matching dice becomes the value of the pool
+    /// let mut pool = Pool::from_faces(&[(4, 6), (2, 6), (4, 6), (6, 6), (4, 6)]);
+    /// PoolOp::CountMatches(4).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 5);
+    /// assert_eq!(pool.sum(), 20);
+    /// assert_eq!(pool.value(), 3);
+    ///
+    /// let gen = dice_nom::parse("10d6=m4 >= 2").unwrap();
+    /// let results = gen.generate(&mut rng);
+    /// let fours = results.lhs.values.iter().filter(|v| v.value == 4).count();
+    /// assert_eq!(results.sum(), (fours >= 2) as i32);
+    ///
+    /// // the value of the pool is its lowest or highest die
+    /// let mut pool = Pool::from_faces(&[(5, 6), (2, 6), (6, 6), (4, 6)]);
+    /// PoolOp::TakeMin.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 4);
+    /// assert_eq!(pool.value(), 2);
+    /// PoolOp::TakeMax.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.value(), 6);
+    ///
+    /// // criticals are marked but still count the same
+    /// let mut pool = Pool::from_faces(&[(20, 20), (12, 20)]);
+    /// PoolOp::Critical.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.crits(), 1);
+    /// assert_eq!(pool.fumbles(), 0);
+    /// assert_eq!(pool.sum(), 32);
+    /// assert_eq!(format!("{}", pool), "20!, 12 = 32");
+    ///
+    /// // sorting only reorders; discarded dice are moved after the kept dice
+    /// let mut pool = Pool::from_faces(&[(3, 6), (6, 6), (1, 6), (4, 6)]);
+    /// pool.values[1].mark_discarded();
+    /// pool.values.push(Value::random_with_value(2, 6, true));
+    /// PoolOp::Sort(true).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(format!("{}", pool), "1, 2*, 3, 4, 6- = 10");
+    /// PoolOp::Sort(false).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(format!("{}", pool), "4, 3, 2*, 1, 6- = 10");
+    ///
+    /// // bonus dice that would push the pool past the cap are discarded
+    /// let mut pool = Pool::new_with_values(vec![val1, val4, val1, val4]);
+    /// for _ in 0..3 {
+    ///     pool.values.push(Value::random_with_value(6, 6, true));
+    /// }
+    /// PoolOp::CapTotal(30).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 7);
+    /// assert_eq!(pool.kept(), 5);
+    /// assert_eq!(pool.sum(), 30);
+    ///
+    /// // the dice originally rolled count even when they exceed the cap
+    /// let mut pool = Pool::from_faces(&[(4, 6), (5, 6)]);
+    /// pool.values.push(Value::random_with_value(6, 6, true));
+    /// PoolOp::CapTotal(3).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert_eq!(pool.sum(), 9);
+    ///
+    /// // a die that always rolls 6 can only pass 30 by exploding; the
+    /// // explosions stop at the cap and the die that would pass it is
+    /// // discarded
+    /// let gen = dice_nom::parse("4d6w[0,0,0,0,0,1]!! cap 30").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.sum(), 30);
+    /// assert_eq!(pool.kept(), 5);
+    /// assert_eq!(pool.count(), 6);
+    ///
+    /// let gen = dice_nom::parse("1d6w[0,0,0,0,0,1]** cap 30").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.sum(), 30);
+    /// assert_eq!(pool.count(), 6);
+    /// assert!(gen.generate_bounded(&mut rng, 6).is_ok());
+    ///
+    /// // the dice rolled so far, bonus dice included, count towards the cap
+    /// let gen = dice_nom::parse("4d6** cap 12").unwrap();
+    /// for _ in 0..100 {
+    ///     let mut total = 0;
+    ///     for v in gen.generate(&mut rng).lhs.values {
+    ///         total += v.sum();
+    ///         assert!(!v.is_bonus() || v.is_discarded() || total <= 12);
+    ///     }
+    /// }
+    ///
+    /// // capped after any explosions; the raw roll still decides explosions
+    /// let mut pool = Pool::from_faces(&[(9, 10), (3, 10)]);
+    /// PoolOp::CapEach(6).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.sum(), 9);
+    /// assert_eq!(format!("{}", pool), "9v, 3 = 9");
+    ///
+    /// let gen = dice_nom::parse("4d10**v6").unwrap();
+    /// for _ in 0..100 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert!(pool.count() >= 4);
+    ///     assert!(pool.values.iter().all(|v| v.sum() <= 6));
+    /// }
+    ///
     /// let mut pool = Pool::new_with_values(vec![val1, val2, val3]);
     /// let old_sum = pool.sum();
     /// PoolOp::Advantage.apply_all(&mut pool, &mut rng);
@@ -631,6 +2523,31 @@ impl PoolOp {
     /// assert_eq!(pool.kept(), 3);
     /// assert!(old_sum >= pool.sum());
     ///
+    /// // a d20 with advantage keeps the higher of exactly two rolls
+    /// let gen = dice_nom::parse("d20 ADV").unwrap();
+    /// for _ in 0..100 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert_eq!(pool.count(), 2);
+    ///     assert_eq!(pool.kept(), 1);
+    ///     assert_eq!(pool.sum(), pool.values.iter().map(|v| v.value).max().unwrap());
+    /// }
+    /// let gen = dice_nom::parse("d20 DIS").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(pool.sum(), pool.values.iter().map(|v| v.value).min().unwrap());
+    ///
+    /// // with more than one die each die is rolled twice, not the pool as a
+    /// // whole: a 20 is always kept even beside a 1
+    /// let mut check = StdRng::seed_from_u64(7);
+    /// let mut seeded = check.clone();
+    /// let second: Vec<i32> = (0..2).map(|_| check.gen_range(0..20) + 1).collect();
+    /// let mut pool = Pool::from_faces(&[(20, 20), (1, 20)]);
+    /// PoolOp::Advantage.apply_all(&mut pool, &mut seeded);
+    /// assert_eq!(pool.count(), 4);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert!(!pool.values[0].is_discarded());
+    /// assert_eq!(pool.sum(), 20 + second[1]);
+    ///
     /// let mut pool = Pool::new_with_values(vec![val1, val2, val3, val4, val5]);
     /// PoolOp::BestGroup.apply_all(&mut pool, &mut rng);
     /// assert_eq!(pool.count(), 5);
@@ -641,35 +2558,192 @@ impl PoolOp {
     /// let mut pool = Pool::new_with_values(vec![val2, val3, val4, val5]);
     /// PoolOp::BestGroup.apply_all(&mut pool, &mut rng);
     /// assert_eq!(pool.sum(), 2);
+    ///
+    /// // two pairs keep the higher pair, wherever it was rolled
+    /// let mut pool = Pool::from_faces(&[(5, 6), (2, 6), (2, 6), (5, 6), (3, 6)]);
+    /// PoolOp::BestGroup.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 2);
+    /// assert_eq!(pool.sum(), 10);
+    ///
+    /// // a larger group beats a higher pair
+    /// let mut pool = Pool::from_faces(&[(1, 6), (6, 6), (1, 6), (6, 6), (1, 6)]);
+    /// PoolOp::BestGroup.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 3);
+    /// assert_eq!(pool.sum(), 3);
+    ///
+    /// // with no repeats the highest single die is kept
+    /// let mut pool = Pool::from_faces(&[(2, 6), (6, 6), (4, 6)]);
+    /// PoolOp::BestGroup.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 1);
+    /// assert_eq!(pool.sum(), 6);
+    ///
+    /// // each die of a mixed pool is at its maximum by its own size: a 6
+    /// // on a d6 and a 7 on a d8 do not explode, a 6 and an 8 do
+    /// let mut pool = Pool::from_faces(&[(6, 6), (7, 8)]);
+    /// PoolOp::Explode(None).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 2);
+    /// let mut pool = Pool::from_faces(&[(6, 6), (8, 8)]);
+    /// PoolOp::Explode(None).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 4);
+    /// assert_eq!(pool.values[2].range, 6);
+    /// assert_eq!(pool.values[3].range, 8);
+    ///
+    /// // `t2` explodes on the top two faces, 7 and 8 of a d8
+    /// for face in 1..=8 {
+    ///     let weights: Vec<String> = (1..=8).map(|n| ((n == face) as i32).to_string()).collect();
+    ///     let gen = dice_nom::parse(&format!("1d8w[{}]!t2", weights.join(","))).unwrap();
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     assert_eq!(pool.count(), if face >= 7 { 2 } else { 1 }, "{}", face);
+    /// }
+    /// let gen = dice_nom::parse("2d6*t2 + 1d20*t2").unwrap();
+    /// for _ in 0..100 {
+    ///     let pool = gen.generate(&mut rng).lhs;
+    ///     let exploded = pool.values.iter().filter(|v| !v.is_bonus() && v.value > v.range - 2).count();
+    ///     assert_eq!(pool.bonus(), exploded);
+    /// }
+    ///
+    /// // constants have no dice to explode or reroll
+    /// for op in [PoolOp::Explode(None), PoolOp::ExplodeUntil(None), PoolOp::Advantage] {
+    ///     let mut pool = Pool::new_with_values(vec![Value::constant(0), Value::constant(3)]);
+    ///     op.apply_all(&mut pool, &mut rng);
+    ///     assert_eq!(pool.count(), 2);
+    ///     assert_eq!(pool.sum(), 3);
+    /// }
+    /// let mut pool = Pool::new_with_values(vec![Value::constant(0)]);
+    /// PoolOp::ExplodeEach(None).apply_last(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 1);
+    /// assert!(dice_nom::parse("3 ! ").is_err());
+    ///
+    /// // a constant among dice is never exploded, discarded or rolled again
+    /// let ops = vec![
+    ///     PoolOp::Explode(None), PoolOp::ExplodeUntil(None), PoolOp::TakeHigh(1),
+    ///     PoolOp::TakeLow(1), PoolOp::TakeMid(1), PoolOp::TakeHighPercent(50),
+    ///     PoolOp::DropLow(1), PoolOp::DropHigh(1), PoolOp::KeepBestPerGroup(2, 1),
+    ///     PoolOp::KeepUnique(1), PoolOp::DropValue(1), PoolOp::KeepValues(vec![6]),
+    ///     PoolOp::Sort(true), PoolOp::Sort(false), PoolOp::CapEach(2),
+    ///     PoolOp::Advantage, PoolOp::Disadvantage, PoolOp::BestGroup,
+    /// ];
+    /// for op in ops {
+    ///     let mut pool = Pool::from_faces(&[(6, 6), (1, 6)]);
+    ///     pool.values.insert(1, Value::constant(1));
+    ///     op.apply_all(&mut pool, &mut rng);
+    ///     let constants: Vec<&Value> = pool.values.iter().filter(|v| v.is_const()).collect();
+    ///     assert_eq!(constants.len(), 1, "{:?}", op);
+    ///     assert!(!constants[0].is_discarded(), "{:?}", op);
+    ///     assert_eq!(constants[0].sum(), 1, "{:?}", op);
+    ///     assert_eq!(pool.values[1], Value::constant(1), "{:?}", op);
+    /// }
+    ///
+    /// // only the dice are kept or rolled again, and only the kept dice
+    /// let mut pool = Pool::from_faces(&[(6, 6), (1, 6), (5, 6)]);
+    /// pool.values.push(Value::constant(10));
+    /// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.kept(), 3);
+    /// assert_eq!(pool.sum(), 21);
+    /// PoolOp::Advantage.apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 6);
+    /// assert_eq!(pool.bonus(), 2);
+    /// let mut pool = Pool::from_faces(&[(6, 6), (6, 6)]);
+    /// pool.values.push(Value::constant(2));
+    /// PoolOp::Explode(None).apply_all(&mut pool, &mut rng);
+    /// assert_eq!(pool.count(), 5);
+    /// assert!(pool.values[3..].iter().all(|v| v.is_random()));
+    /// PoolOp::CountMatches(2).apply_all(&mut pool, &mut rng);
+    /// assert!(pool.value() <= 2);
     /// ```
     pub fn apply_all<R: Rng + ?Sized>(&self, pool: &mut Pool, rng: &mut R) {
-        let cnt = pool.count();
+        self.apply_all_with(pool, &Die::Standard, rng)
+    }
+
+    /// apply_all_with is `apply_all` for pools of any kind of die; any
+    /// new dice are rolled from the faces of `die`.
+    pub fn apply_all_with<R: Rng + ?Sized>(&self, pool: &mut Pool, die: &Die, rng: &mut R) {
+        self.apply_all_bounded(pool, die, rng, MAX_ITERATIONS, None)
+    }
+
+    /// apply_all_bounded is `apply_all_with` where the pool explodes no more
+    /// than `most` times, whatever its own limit, and stops exploding once
+    /// its sum would pass `cap`.
+    fn apply_all_bounded<R: Rng + ?Sized>(
+        &self,
+        pool: &mut Pool,
+        die: &Die,
+        rng: &mut R,
+        most: usize,
+        cap: Option<i32>,
+    ) {
+        match self {
+            PoolOp::ExplosionLimit(op, n) => {
+                op.apply_all_limited(pool, die, rng, explosion_limit(*n).min(most), cap)
+            }
+            _ => self.apply_all_limited(pool, die, rng, MAX_ITERATIONS.min(most), cap),
+        }
+    }
+
+    /// apply_all_limited is `apply_all_with` where the pool explodes at most
+    /// `limit` times.
+    fn apply_all_limited<R: Rng + ?Sized>(
+        &self,
+        pool: &mut Pool,
+        die: &Die,
+        rng: &mut R,
+        limit: usize,
+        cap: Option<i32>,
+    ) {
+        // constants are never exploded, discarded or rerolled; only the
+        // dice are counted
+        let cnt = dice(pool).count();
         if cnt == 0 {
             return;
         }
 
         match self {
-            PoolOp::Explode(n) => {
+            PoolOp::ExplodeTop(op, k) => {
                 let range = pool.range();
-                let n = n.unwrap_or(range);
-                let explode = pool.values.iter().all(|&v| v.value >= n);
+                if let Some(op) = op.with_threshold(range.saturating_sub(*k).saturating_add(1)) {
+                    op.apply_all_limited(pool, die, rng, limit, cap);
+                }
+            }
+
+            PoolOp::Explode(n) => {
+                // a pool of only constants has no dice to explode
+                if pool.range() < 1 {
+                    return;
+                }
+
+                // each die is at its maximum by its own range, so a pool of
+                // mixed dice explodes each die as the size it is
+                let ranges = random_ranges(pool);
+                let explode = limit > 0 && dice(pool).all(|v| v.value >= n.unwrap_or(v.range));
                 if explode {
-                    for _ in 0..cnt {
-                        let roll = Value::random(range, true, rng);
-                        pool.values.push(roll);
+                    for &range in ranges.iter() {
+                        let roll = die.roll(range, true, rng);
+                        if !push_bonus(pool, roll, cap) {
+                            break;
+                        }
                     }
                 }
             }
 
             PoolOp::ExplodeUntil(n) => {
-                let range = pool.range();
-                let n = n.unwrap_or(range);
-                let mut explode = pool.values.iter().all(|&v| v.value >= n);
-                while explode {
-                    for _ in 0..cnt {
-                        let roll = Value::random(range, true, rng);
-                        pool.values.push(roll);
-                        if roll.value < n {
+                if pool.range() < 1 {
+                    return;
+                }
+
+                let ranges = random_ranges(pool);
+                let mut explode = dice(pool).all(|v| v.value >= n.unwrap_or(v.range));
+                for _ in 0..limit {
+                    if !explode {
+                        break;
+                    }
+
+                    for &range in ranges.iter() {
+                        let roll = die.roll(range, true, rng);
+                        if !push_bonus(pool, roll, cap) {
+                            explode = false;
+                            break;
+                        }
+                        if roll.value < n.unwrap_or(range) {
                             explode = false;
                         }
                     }
@@ -677,111 +2751,226 @@ impl PoolOp {
             }
 
             PoolOp::TakeLow(take) => {
-                let take = *take as usize;
+                let take = (*take as usize).max(1);
                 if cnt <= take {
                     return;
                 }
 
-                pool.values.sort_by(|a, b| a.value.cmp(&b.value));
-                for idx in 0..cnt {
-                    if idx >= take {
-                        pool.values[idx].mark_discarded();
-                    }
+                // dice already discarded sort last, so the die that is kept
+                // is one that was kept before
+                sort_dice_by_key(pool, |v| (v.is_discarded(), v.value));
+                for idx in dice_idxs(pool).into_iter().skip(take) {
+                    pool.values[idx].mark_discarded();
                 }
             }
 
-            PoolOp::TakeMid(take) => {
+            PoolOp::TakeMid(take) | PoolOp::TakeMidLow(take) => {
+                // the middle is of the dice still kept, which sort first
                 let take = *take as usize;
+                let cnt = kept_count(pool);
                 if cnt <= take {
                     return;
                 }
 
-                pool.values.sort_by(|a, b| b.value.cmp(&a.value));
-                let skip_start = (cnt - take) / 2;
+                // when an odd number of dice are discarded the extra die is
+                // a low one for `TakeMid` and a high one for `TakeMidLow`
+                sort_dice_by_key(pool, |v| (v.is_discarded(), std::cmp::Reverse(v.value)));
+                let skip_start = match self {
+                    PoolOp::TakeMidLow(_) => (cnt - take).div_ceil(2),
+                    _ => (cnt - take) / 2,
+                };
                 let skip_end = skip_start + take;
-                for idx in 0..cnt {
-                    if idx < skip_start || idx >= skip_end {
+                for (n, idx) in dice_idxs(pool).into_iter().enumerate() {
+                    if n < skip_start || n >= skip_end {
                         pool.values[idx].mark_discarded();
                     }
                 }
             }
 
             PoolOp::TakeHigh(take) => {
-                let take = *take as usize;
+                let take = (*take as usize).max(1);
                 if cnt <= take {
                     return;
                 }
 
-                pool.values.sort_by(|a, b| b.value.cmp(&a.value));
-                for idx in 0..cnt {
-                    if idx >= take {
-                        pool.values[idx].mark_discarded();
-                    }
+                sort_dice_by_key(pool, |v| (v.is_discarded(), std::cmp::Reverse(v.value)));
+                for idx in dice_idxs(pool).into_iter().skip(take) {
+                    pool.values[idx].mark_discarded();
                 }
             }
 
-            PoolOp::Advantage => {
-                let old = pool.sum();
-                let range = pool.range();
-                for _ in 0..cnt {
-                    let roll = Value::random(range, true, rng);
-                    pool.values.push(roll);
+            PoolOp::TakeLowPercent(pct) => {
+                PoolOp::TakeLow(percent_of(kept_count(pool), *pct)).apply_all_with(pool, die, rng);
+            }
+
+            PoolOp::TakeHighPercent(pct) => {
+                PoolOp::TakeHigh(percent_of(kept_count(pool), *pct)).apply_all_with(pool, die, rng);
+            }
+
+            PoolOp::DropLow(drop) => {
+                // the drop is from the dice still kept, so it follows an
+                // earlier take or drop; dropping every die (or more) still
+                // keeps the best one
+                let keep = kept_count(pool).saturating_sub((*drop).max(0) as usize).max(1);
+                sort_dice_by_key(pool, |v| (v.is_discarded(), std::cmp::Reverse(v.value)));
+                for idx in dice_idxs(pool).into_iter().skip(keep) {
+                    pool.values[idx].mark_discarded();
+                }
+            }
+
+            PoolOp::DropHigh(drop) => {
+                let keep = kept_count(pool).saturating_sub((*drop).max(0) as usize).max(1);
+                sort_dice_by_key(pool, |v| (v.is_discarded(), v.value));
+                for idx in dice_idxs(pool).into_iter().skip(keep) {
+                    pool.values[idx].mark_discarded();
+                }
+            }
+
+            PoolOp::KeepBestPerGroup(size, keep) => {
+                if *size < 1 {
+                    return;
                 }
 
-                if pool.sum() > old * 2 {
-                    for idx in 0..cnt {
+                // groups are made in roll order; the pool is not reordered
+                for group in dice_idxs(pool).chunks(*size as usize) {
+                    let mut idxs: Vec<usize> = group
+                        .iter()
+                        .copied()
+                        .filter(|&idx| !pool.values[idx].is_discarded())
+                        .collect();
+                    idxs.sort_by_key(|&idx| std::cmp::Reverse(pool.values[idx].value));
+                    for &idx in idxs.iter().skip((*keep).max(0) as usize) {
                         pool.values[idx].mark_discarded();
                     }
-                } else {
-                    for idx in cnt..cnt * 2 {
-                        pool.values[idx].mark_discarded();
+                }
+            }
+
+            PoolOp::KeepUnique(take) => {
+                let take = *take as usize;
+                let mut seen: Vec<i32> = Vec::new();
+                for v in &mut pool.values {
+                    if v.is_discarded() || v.is_const() {
+                        continue;
+                    }
+
+                    if seen.len() < take && !seen.contains(&v.value) {
+                        seen.push(v.value);
+                    } else {
+                        v.mark_discarded();
                     }
                 }
             }
 
-            PoolOp::Disadvantage => {
-                let old = pool.sum();
-                let range = pool.range();
-                for _ in 0..cnt {
-                    let roll = Value::random(range, true, rng);
-                    pool.values.push(roll);
+            PoolOp::DropValue(n) => {
+                for v in &mut pool.values {
+                    if v.is_random() && v.value == *n {
+                        v.mark_discarded();
+                    }
                 }
+            }
 
-                if pool.sum() > old * 2 {
-                    for idx in cnt..cnt * 2 {
-                        pool.values[idx].mark_discarded();
+            PoolOp::KeepValues(values) => {
+                for v in &mut pool.values {
+                    if v.is_random() && !values.contains(&v.value) {
+                        v.mark_discarded();
+                    }
+                }
+            }
+
+            PoolOp::TakeMin | PoolOp::TakeMax => {
+                // every die is still kept; only the value of the pool changes
+                let kept = dice(pool).filter(|v| !v.is_discarded()).map(|v| v.sum());
+                let value = match self {
+                    PoolOp::TakeMin => kept.min(),
+                    _ => kept.max(),
+                };
+                pool.set_value(value.unwrap_or(0));
+            }
+
+            PoolOp::CountMatches(n) => {
+                let matches = dice(pool).filter(|v| !v.is_discarded() && v.value == *n).count();
+                pool.set_value(matches as i32);
+            }
+
+            PoolOp::Critical => {
+                for v in &mut pool.values {
+                    if v.is_random() && v.value == v.range {
+                        v.mark_crit();
                     }
+                }
+            }
+
+            PoolOp::Sort(ascending) => {
+                // kept dice (bonus dice included) come first, followed by the
+                // discarded dice; each part is sorted by value. Constants
+                // stay where they are.
+                if *ascending {
+                    sort_dice_by_key(pool, |v| (v.is_discarded(), v.value));
                 } else {
-                    for idx in 0..cnt {
-                        pool.values[idx].mark_discarded();
+                    sort_dice_by_key(pool, |v| (v.is_discarded(), std::cmp::Reverse(v.value)));
+                }
+            }
+
+            PoolOp::CapTotal(cap) => {
+                // the explosions of the operators before the cap already
+                // stop at it (see `push_bonus`); this catches bonus dice
+                // added some other way, e.g. applied to a pool by hand. The
+                // dice originally rolled always count.
+                let mut total = 0i32;
+                for v in &mut pool.values {
+                    if v.is_bonus() && !v.is_discarded() && total.saturating_add(v.sum()) > *cap {
+                        v.mark_discarded();
                     }
+                    total = total.saturating_add(v.sum());
                 }
             }
 
-            PoolOp::BestGroup => {
-                pool.values.sort_by(|a, b| b.value.cmp(&a.value));
-                let mut last_val = 0;
-                let mut max_val = 0;
-                let mut max_run = 0;
-                let mut curr_run = 0;
-                for idx in 0..cnt {
-                    let val = pool.values[idx];
-                    if !val.is_discarded() {
-                        if last_val == val.value {
-                            curr_run += 1;
-                            if curr_run > max_run {
-                                max_run = curr_run;
-                                max_val = last_val;
-                            }
-                        } else {
-                            last_val = val.value;
-                            curr_run = 0;
-                        }
+            PoolOp::CapEach(cap) => {
+                for v in &mut pool.values {
+                    if v.is_random() {
+                        v.set_cap(*cap);
                     }
                 }
+            }
+
+            PoolOp::Advantage | PoolOp::Disadvantage => {
+                // each kept die is rolled a second time, as the size it is,
+                // and the higher (or lower) of the two rolls is kept; the
+                // second rolls are listed after the pool and ties keep the
+                // first roll
+                let idxs: Vec<usize> = dice_idxs(pool)
+                    .into_iter()
+                    .filter(|&idx| !pool.values[idx].is_discarded())
+                    .collect();
+                for idx in idxs {
+                    let first = pool.values[idx];
+                    let second = die.roll(first.range, true, rng);
+                    pool.values.push(second);
+
+                    let keep_second = match self {
+                        PoolOp::Advantage => second.value > first.value,
+                        _ => second.value < first.value,
+                    };
+                    let loser = if keep_second { idx } else { pool.count() - 1 };
+                    pool.values[loser].mark_discarded();
+                }
+            }
 
+            PoolOp::BestGroup => {
+                let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+                for v in dice(pool).filter(|v| !v.is_discarded()) {
+                    *counts.entry(v.value).or_insert(0) += 1;
+                }
+
+                // the largest group wins; ties go to the higher value
+                let best = counts
+                    .iter()
+                    .max_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)))
+                    .map(|(&value, _)| value);
+
+                sort_dice_by_key(pool, |v| std::cmp::Reverse(v.value));
                 for v in &mut pool.values {
-                    if v.value != max_val {
+                    if v.is_random() && Some(v.value) != best {
                         v.mark_discarded();
                     }
                 }
@@ -790,3 +2979,21 @@ impl PoolOp {
         }
     }
 }
+
+/// push_bonus adds a bonus die to the pool. When it would take the pool
+/// sum past `cap` it is discarded instead and push_bonus is false, so the
+/// explosion stops there.
+fn push_bonus(pool: &mut Pool, mut roll: Value, cap: Option<i32>) -> bool {
+    let over = cap.is_some_and(|cap| pool.sum().saturating_add(roll.sum()) > cap);
+    if over {
+        roll.mark_discarded();
+    }
+    pool.values.push(roll);
+    !over
+}
+
+/// percent_of returns `pct` percent of `cnt`, rounded to the nearest whole die.
+/// A result too large for an `i32` is clamped.
+fn percent_of(cnt: usize, pct: i32) -> i32 {
+    ((cnt as i64 * pct as i64 + 50) / 100).min(i32::MAX as i64) as i32
+}
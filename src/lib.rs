@@ -1,10 +1,16 @@
 pub mod results;
 
 pub mod generators;
-use generators::{ Generator, PoolGenerator };
+use generators::{ Condition, Count, Die, Generator, PoolGenerator };
+use results::Results;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 pub mod parsers;
 
+pub mod stats;
+
 /// roller builds a simple `PoolGenerator` that can randomly generate dice rolls.
 ///
 /// * Examples
@@ -13,28 +19,28 @@ pub mod parsers;
 /// use rand::prelude::*;
 /// let mut rng = rand::thread_rng();
 /// let roller = dice_nom::roller(3, 6, Some("**"));
-/// assert_eq!(roller.count, 3);
+/// assert_eq!(roller.count, dice_nom::generators::Count::Fixed(3));
 /// assert_eq!(roller.range, 6);
-/// assert_eq!(roller.op, Some(dice_nom::generators::PoolOp::ExplodeEachUntil(None)));
+/// assert_eq!(roller.ops, vec![dice_nom::generators::PoolOp::ExplodeEachUntil(None)]);
 /// 
 /// let pool = roller.generate(&mut rng);
 /// assert!(pool.count() >= 3);
 /// assert!(pool.sum() >= 3);
 /// ```
 pub fn roller(count: i32, range: i32, op: Option<&str>) -> PoolGenerator {
-    let op = match op {
-        Some(s) => match parsers::pool_op_parser(s) {
-            Ok((_, op)) => Some(op),
-            Err(_) => None,
+    let ops = match op {
+        Some(s) => match parsers::pool_ops_parser(s) {
+            Ok((_, ops)) => ops,
+            Err(_) => vec![],
         },
-        None => None,
+        None => vec![],
     };
-    PoolGenerator{ count, range, op}
+    PoolGenerator{ count: Count::Fixed(count), range, die: Die::Standard, ops }
 }
 
-/// parse builds a generator from the given input string. If any of the string
-/// can be parsed a generator is returned. If no generator can be built then
-/// an error is returned with the input string.
+/// parse builds a generator from the given input string. The whole string
+/// must be parsed (trailing whitespace is allowed); if anything is left over
+/// an error is returned with the part of the input that could not be parsed.
 /// 
 /// * Examples
 /// 
@@ -47,14 +53,213 @@ pub fn roller(count: i32, range: i32, op: Option<&str>) -> PoolGenerator {
 ///     let results = gen.generate(&mut rng);
 ///     assert!(!results.rhs.is_none());
 /// }
-/// 
+///
+/// let gen = dice_nom::parse("3d[0,0,1,1,2,3]!").unwrap();
+/// assert_eq!(format!("{}", gen), "3d[0,0,1,1,2,3]!");
+///
 /// let gen = dice_nom::parse("attack badger");
 /// assert!(!gen.is_ok());
 /// assert_eq!(gen, Err("attack badger"));
+///
+/// assert_eq!(dice_nom::parse("2d6 xyz"), Err(" xyz"));
+/// assert!(dice_nom::parse("2d6 ").is_ok());
+///
+/// // dice need at least one face, though a pool may have no dice
+/// assert_eq!(dice_nom::parse("d0"), Err("d0"));
+/// assert_eq!(dice_nom::parse("0d0"), Err("0d0"));
+/// assert_eq!(dice_nom::parse("0d6").unwrap().generate(&mut rng).sum(), 0);
+///
+/// // a d1 always explodes, but only until the iteration cap
+/// let gen = dice_nom::parse("1d1!!").unwrap();
+/// assert_eq!(gen.generate(&mut rng).sum(), 1001);
+///
+/// // the input is kept as it was written
+/// let gen = dice_nom::parse("2d6  +3 ").unwrap();
+/// assert_eq!(gen.source.as_deref(), Some("2d6  +3 "));
+/// assert_eq!(format!("{}", gen), "2d6 + 3");
 /// ```
 pub fn parse(input: &str) -> Result<Generator, &str> {
     match parsers::generator_parser(input) {
-        Ok((_, gen)) => Ok(gen),
+        Ok((rest, mut gen)) if rest.trim().is_empty() => {
+            gen.source = Some(input.to_string());
+            Ok(gen)
+        }
+        Ok((rest, _)) => Err(rest),
         Err(_) => Err(input),
     }
 }
+
+/// parse_verbose is `parse` with a `ParseError` that says where the input
+/// went wrong and what was expected there, e.g. to show a caret under the
+/// bad character.
+///
+/// * Examples
+///
+/// ```
+/// let gen = dice_nom::parse_verbose("3d6 + 2").unwrap();
+/// assert_eq!(gen.source.as_deref(), Some("3d6 + 2"));
+///
+/// // the right hand side of the comparison is missing
+/// let err = dice_nom::parse_verbose("2d6 >").unwrap_err();
+/// assert_eq!(err.offset, 5);
+/// assert_eq!(err.rest, "");
+/// assert_eq!(err.context, vec!["the right hand side of the comparison"]);
+/// assert_eq!(format!("{}", err), "line 1, offset 5: expected the right hand side of the comparison");
+///
+/// let err = dice_nom::parse_verbose("2d6 xyz").unwrap_err();
+/// assert_eq!(err.offset, 4);
+/// assert_eq!(format!("{}", err), "line 1, offset 4: expected the end of the expression, found `xyz`");
+///
+/// // a caret under the bad character
+/// let input = "1d20 + 5 >= d";
+/// let err = dice_nom::parse_verbose(input).unwrap_err();
+/// let caret = format!("{}\n{}^", input, " ".repeat(err.offset));
+/// assert_eq!(caret, "1d20 + 5 >= d\n            ^");
+/// ```
+pub fn parse_verbose(input: &str) -> Result<Generator, ParseError> {
+    match parsers::generator_verbose_parser(input) {
+        Ok((_, mut gen)) => {
+            gen.source = Some(input.to_string());
+            Ok(gen)
+        }
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError::new(input, e)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError {
+            line: 1,
+            offset: input.len(),
+            rest: String::new(),
+            context: vec![],
+        }),
+    }
+}
+
+/// parse_many builds a generator from every expression in the input, one
+/// per line or several on a line separated by `;`. Blank lines (and blanks
+/// between `;`) are skipped. An expression that cannot be parsed is a
+/// `ParseError` with its line number and its offset in that line; the rest
+/// are still parsed.
+///
+/// * Examples
+///
+/// ```
+/// let gens = dice_nom::parse_many("3d6\nattack badger\n1d20 + 5");
+/// assert_eq!(gens.len(), 3);
+/// assert!(gens[0].is_ok());
+/// let err = gens[1].as_ref().unwrap_err();
+/// assert_eq!(err.line, 2);
+/// assert_eq!(err.offset, 0);
+/// assert_eq!(format!("{}", gens[2].as_ref().unwrap()), "1d20 + 5");
+///
+/// let gens = dice_nom::parse_many("2d6; 1d8 xyz\n\n  4d6^3 ;\n");
+/// assert_eq!(gens.len(), 3);
+/// let err = gens[1].as_ref().unwrap_err();
+/// assert_eq!((err.line, err.offset, err.rest.as_str()), (1, 9, "xyz"));
+/// assert_eq!(gens[2].as_ref().unwrap().source.as_deref(), Some("4d6^3"));
+/// ```
+pub fn parse_many(input: &str) -> Vec<Result<Generator, ParseError>> {
+    let mut gens = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        for expr in line.split(';').map(str::trim).filter(|expr| !expr.is_empty()) {
+            // where the expression starts in the line
+            let start = expr.as_ptr() as usize - line.as_ptr() as usize;
+            gens.push(parse_verbose(expr).map_err(|e| ParseError {
+                line: idx + 1,
+                offset: start + e.offset,
+                ..e
+            }));
+        }
+    }
+    gens
+}
+
+/// ParseError is where an expression could not be parsed: the line it is
+/// on (counting from 1), the byte offset in that line, the part of the
+/// expression that was left over and what was expected there, innermost
+/// first.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub offset: usize,
+    pub rest: String,
+    pub context: Vec<&'static str>,
+}
+
+impl ParseError {
+    fn new(input: &str, e: nom::error::VerboseError<&str>) -> ParseError {
+        // the first error is the deepest one
+        let rest = e.errors.first().map(|(rest, _)| *rest).unwrap_or(input);
+        let context = e
+            .errors
+            .iter()
+            .filter_map(|(_, kind)| match kind {
+                nom::error::VerboseErrorKind::Context(name) => Some(*name),
+                _ => None,
+            })
+            .collect();
+        ParseError { line: 1, offset: input.len() - rest.len(), rest: rest.to_string(), context }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let expected = self.context.first().copied().unwrap_or("a dice expression");
+        write!(f, "line {}, offset {}: expected {}", self.line, self.offset, expected)?;
+        if !self.rest.is_empty() {
+            write!(f, ", found `{}`", self.rest)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// parse_condition builds a condition from the given input: generators
+/// combined with `&` (both must pass) and `|` (either must pass). As with
+/// `parse` the whole string must be parsed.
+///
+/// * Examples
+///
+/// ```
+/// let cond = dice_nom::parse_condition("2d6 >= 8 & 1d20 >= 15").unwrap();
+/// assert_eq!(format!("{}", cond), "2d6 >= 8 & 1d20 >= 15");
+/// assert_eq!(dice_nom::parse_condition("2d6 >= 8 &"), Err(" &"));
+/// ```
+pub fn parse_condition(input: &str) -> Result<Condition, &str> {
+    match parsers::condition_parser(input) {
+        Ok((rest, cond)) if rest.trim().is_empty() => Ok(cond),
+        Ok((rest, _)) => Err(rest),
+        Err(_) => Err(input),
+    }
+}
+
+/// Roller parses an expression once and rolls it as often as needed with
+/// its own seeded random number generator.
+///
+/// * Examples
+///
+/// ```
+/// let mut roller = dice_nom::Roller::new("3d6 + 2", 42).unwrap();
+/// let mut again = dice_nom::Roller::new("3d6 + 2", 42).unwrap();
+/// for _ in 0..10 {
+///     let results = roller.roll();
+///     assert!(results.sum() >= 5 && results.sum() <= 20);
+///     assert_eq!(results.sum(), again.roll().sum());
+/// }
+///
+/// assert!(dice_nom::Roller::new("attack badger", 42).is_err());
+/// ```
+#[derive(Debug)]
+pub struct Roller {
+    pub gen: Generator,
+    rng: StdRng,
+}
+
+impl Roller {
+    pub fn new(input: &str, seed: u64) -> Result<Roller, &str> {
+        let gen = parse(input)?;
+        Ok(Roller { gen, rng: StdRng::seed_from_u64(seed) })
+    }
+
+    pub fn roll(&mut self) -> Results {
+        self.gen.generate(&mut self.rng)
+    }
+}
@@ -2,18 +2,20 @@ extern crate nom;
 
 use nom::{
     branch::alt,
-    bytes::complete::{is_a, tag},
-    character::complete::{char, digit0, digit1, space0},
-    combinator::opt,
-    multi::fold_many1,
-    sequence::{delimited, preceded, separated_pair, tuple},
+    bytes::complete::{is_a, tag, take_while1},
+    character::complete::{char, digit1, one_of, space0},
+    combinator::{cut, eof, map_res, not, opt, verify},
+    error::{context, ParseError, VerboseError},
+    multi::{fold_many1, many0, separated_list1},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
 use super::generators::{
-    ArithOp, ArithTermGenerator, ComparisonOp, ExprGenerator, Generator, HitsGenerator,
+    ArithOp, ArithTermGenerator, ComparisonOp, Condition, Count, Die, ExprGenerator, Generator, HitsGenerator,
     PoolGenerator, PoolOp, SuccGenerator, SuccessOp, TargetOp, TermGenerator,
 };
+use super::results::{Decimal, RoundingMode};
 
 /// generator_parser is the top level parser and builds a generator
 /// that can compare the relative values of two sub expressions.
@@ -37,10 +39,12 @@ use super::generators::{
 ///                     ArithTermGenerator{
 ///                         op: ArithOp::ImplicitAdd,
 ///                         term: TermGenerator::Pool(PoolGenerator {
-///                             count: 4,
+///                             count: Count::Fixed(4),
 ///                             range: 6,
-///                             op: None    
-///                         })
+///                             die: Die::Standard,
+///                             ops: vec![]
+///                         }),
+///                         label: None
 ///                     }
 ///                 ]
 ///             },
@@ -52,11 +56,106 @@ use super::generators::{
 /// ```
 pub fn generator_parser(input: &str) -> IResult<&str, Generator> {
     match tuple((succ_gen_parser, opt(comparison_op_parser)))(input) {
-        Ok((input, (succ, op))) => Ok((input, Generator { succ, op })),
+        Ok((input, (succ, op))) => Ok((input, Generator { succ, op, source: None })),
         Err(e) => Err(e),
     }
 }
 
+/// generator_verbose_parser is `generator_parser` for diagnostics: the
+/// whole input must be parsed, and a failure is a `VerboseError` naming
+/// the part of the expression that was expected (`an expression`, `the
+/// right hand side of the comparison` or `the end of the expression`).
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::parsers::generator_verbose_parser;
+/// use nom::error::VerboseErrorKind;
+/// let (input, gen) = generator_verbose_parser("3d8 > 4d6 ").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(format!("{}", gen), "3d8 > 4d6");
+///
+/// let err = match generator_verbose_parser("2d6 >") {
+///     Err(nom::Err::Failure(err)) => err,
+///     _ => panic!("the right hand side is missing"),
+/// };
+/// assert_eq!(err.errors[0].0, "");
+/// assert!(err.errors.contains(&("", VerboseErrorKind::Context("the right hand side of the comparison"))));
+/// ```
+pub fn generator_verbose_parser(input: &str) -> IResult<&str, Generator, VerboseError<&str>> {
+    match tuple((
+        context("an expression", verbose(succ_gen_parser)),
+        opt(tuple((
+            verbose(comparison_tag_parser),
+            cut(context("the right hand side of the comparison", verbose(succ_gen_parser))),
+        ))),
+        context("the end of the expression", terminated(space0, eof)),
+    ))(input)
+    {
+        Ok((input, (succ, op, _))) => {
+            let op = op.map(|(tag, rhs)| comparison_op(tag, rhs));
+            Ok((input, Generator { succ, op, source: None }))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// verbose turns the errors of a parser into a `VerboseError`.
+fn verbose<'a, O, F>(parser: F) -> impl Fn(&'a str) -> IResult<&'a str, O, VerboseError<&'a str>>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O>,
+{
+    move |input| parser(input).map_err(|e| e.map(|e| VerboseError::from_error_kind(e.input, e.code)))
+}
+
+/// condition_parser combines generators with `&` and `|`; `&` binds
+/// tighter than `|`.
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::parsers::condition_parser;
+/// use dice_nom::generators::Condition;
+/// let (input, cond) = condition_parser("2d6 >= 8 & 1d20 >= 15 | 1d4 = 4").unwrap();
+/// assert_eq!(input, "");
+/// match cond {
+///     Condition::Or(lhs, rhs) => {
+///         assert!(matches!(*lhs, Condition::And(_, _)));
+///         assert!(matches!(*rhs, Condition::Roll(_)));
+///     }
+///     _ => panic!("expected an or"),
+/// }
+///
+/// let (input, cond) = condition_parser("2d6").unwrap();
+/// assert_eq!(input, "");
+/// assert!(matches!(cond, Condition::Roll(_)));
+/// ```
+pub fn condition_parser(input: &str) -> IResult<&str, Condition> {
+    match separated_list1(delimited(space0, char('|'), space0), and_condition_parser)(input) {
+        Ok((input, conds)) => Ok((input, fold_conditions(conds, Condition::Or))),
+        Err(e) => Err(e),
+    }
+}
+
+fn and_condition_parser(input: &str) -> IResult<&str, Condition> {
+    match separated_list1(delimited(space0, char('&'), space0), generator_parser)(input) {
+        Ok((input, gens)) => {
+            let conds = gens.into_iter().map(Condition::Roll).collect();
+            Ok((input, fold_conditions(conds, Condition::And)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn fold_conditions(
+    conds: Vec<Condition>,
+    combine: fn(Box<Condition>, Box<Condition>) -> Condition,
+) -> Condition {
+    let mut conds = conds.into_iter();
+    let first = conds.next().unwrap();
+    conds.fold(first, |lhs, rhs| combine(Box::new(lhs), Box::new(rhs)))
+}
+
 /// succ_parser builds a generator from the input that returns the
 /// level of success of the sum of the rolled dice.
 ///
@@ -77,9 +176,18 @@ pub fn generator_parser(input: &str) -> IResult<&str, Generator> {
 /// let (input, succ) = succ_gen_parser("10d6(4){3, 2}").unwrap();
 /// assert_eq!(input, "");
 /// assert_eq!(succ.op, Some(SuccessOp::TargetSuccNext(3, 2)));
+///
+/// // roll 2d8 exploding, one success for every 4 on each die
+/// let (input, succ) = succ_gen_parser("2d8**{{4}}").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(succ.op, Some(SuccessOp::PerDie(4, None)));
 /// ```
 pub fn succ_gen_parser(input: &str) -> IResult<&str, SuccGenerator> {
-    match tuple((hits_parser, opt(alt((succ_op_parser, succ_next_op_parser)))))(input) {
+    match tuple((
+        hits_parser,
+        opt(alt((per_die_op_parser, tiers_op_parser, succ_op_parser, succ_next_op_parser))),
+    ))(input)
+    {
         Ok((input, (hits, op))) => Ok((input, SuccGenerator { hits, op })),
         Err(e) => Err(e),
     }
@@ -112,15 +220,31 @@ pub fn hits_parser(input: &str) -> IResult<&str, HitsGenerator> {
 
 fn pare_parser(input: &str) -> IResult<&str, ExprGenerator> {
     alt((
+        ungroup_parser,
         delimited(
             tuple((space0, char('('), space0)),
             expr_parser,
             tuple((space0, char(')'), space0)),
         ),
-        expr_parser,
     ))(input)
 }
 
+/// ungroup_parser removes the parentheses around an expression that is
+/// nothing but a single group, e.g. `(2d4 + 3d6)[4]`.
+fn ungroup_parser(input: &str) -> IResult<&str, ExprGenerator> {
+    match expr_parser(input) {
+        Ok((input, mut expr)) => {
+            if expr.terms.len() == 1 {
+                if let TermGenerator::Group(group) = &expr.terms[0].term {
+                    expr = (**group).clone();
+                }
+            }
+            Ok((input, expr))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 /// expr_parser builds a vector of terms
 ///
 /// * Examples
@@ -128,12 +252,55 @@ fn pare_parser(input: &str) -> IResult<&str, ExprGenerator> {
 /// ```
 /// use dice_nom::parsers::expr_parser;
 /// use dice_nom::generators::*;
+/// use dice_nom::results::RoundingMode;
 /// let (input, expr) = expr_parser("3d4 + 2d6 - d8").unwrap();
 /// assert_eq!(input, "");
 /// assert_eq!(expr.terms.len(), 3);
 /// assert_eq!(expr.terms[0].op, ArithOp::ImplicitAdd);
 /// assert_eq!(expr.terms[1].op, ArithOp::Add);
 /// assert_eq!(expr.terms[2].op, ArithOp::Sub);
+///
+/// // `*` right after a pool is explode each; with a space it multiplies
+/// let (input, expr) = expr_parser("2d6 * 3 / 2").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(expr.terms[1].op, ArithOp::Mul);
+/// assert_eq!(expr.terms[2].op, ArithOp::Div(RoundingMode::TowardZero));
+/// assert_eq!(format!("{}", expr), "2d6 * 3 / 2");
+///
+/// let (_, expr) = expr_parser("(3d6)\\2 + 1d6 \\^ 2").unwrap();
+/// assert_eq!(expr.terms[1].op, ArithOp::Div(RoundingMode::Down));
+/// assert_eq!(expr.terms[3].op, ArithOp::Div(RoundingMode::Up));
+/// assert_eq!(format!("{}", expr), "(3d6) \\ 2 + 1d6 \\^ 2");
+///
+/// let (_, expr) = expr_parser("7 \\~ 2").unwrap();
+/// assert_eq!(expr.terms[1].op, ArithOp::Div(RoundingMode::Nearest));
+/// assert_eq!(format!("{}", expr), "7 \\~ 2");
+///
+/// let (_, expr) = expr_parser("2d6*3").unwrap();
+/// assert_eq!(expr.terms.len(), 1);
+///
+/// // parentheses group an expression into a single term
+/// let (input, expr) = expr_parser("2 * (1d6 + 1d4)").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(expr.terms.len(), 2);
+/// assert!(matches!(expr.terms[1].term, TermGenerator::Group(_)));
+///
+/// let (input, expr) = expr_parser("((2d6 + 3) * 2 - (1d4 / 2)) * 3").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(format!("{}", expr), "((2d6 + 3) * 2 - (1d4 / 2)) * 3");
+///
+/// let mut rng = rand::thread_rng();
+/// let (_, expr) = expr_parser("((1 + 2) * (3 - 1)) * 2").unwrap();
+/// assert_eq!(expr.generate(&mut rng).value(), 12);
+///
+/// let (_, expr) = expr_parser("10 - (6 - (1d1 + 2))").unwrap();
+/// assert_eq!(expr.generate(&mut rng).value(), 7);
+///
+/// // terms may be tagged with a label
+/// let (_, expr) = expr_parser("2d6#slashing + 1d8#fire").unwrap();
+/// assert_eq!(expr.terms[0].label, Some(String::from("slashing")));
+/// assert_eq!(expr.terms[1].label, Some(String::from("fire")));
+/// assert_eq!(format!("{}", expr), "2d6#slashing + 1d8#fire");
 /// ```
 pub fn expr_parser(input: &str) -> IResult<&str, ExprGenerator> {
     match fold_many1(
@@ -157,6 +324,7 @@ fn implicit_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
             ArithTermGenerator {
                 op: ArithOp::ImplicitAdd,
                 term,
+                label: None,
             },
         )),
         Err(e) => Err(e),
@@ -170,6 +338,7 @@ fn add_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
             ArithTermGenerator {
                 op: ArithOp::Add,
                 term,
+                label: None,
             },
         )),
         Err(e) => Err(e),
@@ -183,14 +352,85 @@ fn sub_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
             ArithTermGenerator {
                 op: ArithOp::Sub,
                 term,
+                label: None,
+            },
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+fn mul_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
+    match preceded(delimited(space0, char('*'), space0), term_parser)(input) {
+        Ok((input, term)) => Ok((
+            input,
+            ArithTermGenerator {
+                op: ArithOp::Mul,
+                term,
+                label: None,
             },
         )),
         Err(e) => Err(e),
     }
 }
 
+/// div_term_parser reads a division and how it rounds: `/` toward zero,
+/// `\\` down, `\\^` up and `\\~` to the nearest.
+fn div_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
+    match tuple((
+        delimited(space0, alt((tag("\\^"), tag("\\~"), tag("\\"), tag("/"))), space0),
+        term_parser,
+    ))(input)
+    {
+        Ok((input, (op, term))) => {
+            let mode = match op {
+                "\\^" => RoundingMode::Up,
+                "\\~" => RoundingMode::Nearest,
+                "\\" => RoundingMode::Down,
+                _ => RoundingMode::TowardZero,
+            };
+            Ok((
+                input,
+                ArithTermGenerator {
+                    op: ArithOp::Div(mode),
+                    term,
+                    label: None,
+                },
+            ))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 fn arith_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
-    alt((implicit_term_parser, add_term_parser, sub_term_parser))(input)
+    match tuple((
+        alt((
+            implicit_term_parser,
+            add_term_parser,
+            sub_term_parser,
+            mul_term_parser,
+            div_term_parser,
+        )),
+        opt(label_parser),
+    ))(input)
+    {
+        Ok((input, (mut arith_term, label))) => {
+            arith_term.label = label;
+            Ok((input, arith_term))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// label_parser reads the tag on a term, e.g. the `#fire` in `1d8#fire`.
+fn label_parser(input: &str) -> IResult<&str, String> {
+    match preceded(
+        char('#'),
+        take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+    )(input)
+    {
+        Ok((input, chars)) => Ok((input, String::from(chars))),
+        Err(e) => Err(e),
+    }
 }
 
 /// `term_parser` builds a `TermGenerator` from the given input.
@@ -199,56 +439,343 @@ fn arith_term_parser(input: &str) -> IResult<&str, ArithTermGenerator> {
 ///
 /// ```
 /// use dice_nom::parsers::term_parser;
-/// use dice_nom::generators::{TermGenerator, PoolGenerator, PoolOp};
+/// use dice_nom::generators::{Count, Die, TermGenerator, PoolGenerator, PoolOp};
 /// assert_eq!(term_parser("10 "), Ok((" ", TermGenerator::Constant(10))));
 /// assert_eq!(term_parser("2d6**"), Ok((
 ///     "",
 ///     TermGenerator::Pool(PoolGenerator{
-///         count: 2,
+///         count: Count::Fixed(2),
 ///         range: 6,
-///         op: Some(PoolOp::ExplodeEachUntil(None)) }))
+///         die: Die::Standard,
+///         ops: vec![PoolOp::ExplodeEachUntil(None)] }))
 /// ));
 /// assert_eq!(term_parser("3d10!!4"), Ok((
 ///     "",
 ///     TermGenerator::Pool(PoolGenerator{
-///         count: 3,
+///         count: Count::Fixed(3),
 ///         range: 10,
-///         op: Some(PoolOp::ExplodeUntil(Some(4))) }))
+///         die: Die::Standard,
+///         ops: vec![PoolOp::ExplodeUntil(Some(4))] }))
+/// ));
+/// assert_eq!(term_parser("4dF"), Ok((
+///     "",
+///     TermGenerator::Pool(PoolGenerator{
+///         count: Count::Fixed(4),
+///         range: 1,
+///         die: Die::Fudge,
+///         ops: vec![] }))
+/// ));
+/// assert_eq!(term_parser("3d[0,0,1,1,2,3]!"), Ok((
+///     "",
+///     TermGenerator::Pool(PoolGenerator{
+///         count: Count::Fixed(3),
+///         range: 3,
+///         die: Die::Faces(vec![0, 0, 1, 1, 2, 3]),
+///         ops: vec![PoolOp::Explode(None)] }))
 /// ));
 /// ```
 pub fn term_parser(input: &str) -> IResult<&str, TermGenerator> {
-    alt((pool_parser, const_parser))(input)
+    alt((pool_parser, repeat_parser, group_parser, decimal_const_parser, const_parser))(input)
+}
+
+/// repeat_parser reads an expression to be rolled a number of times, e.g.
+/// `3x(1d20 + 5)`.
+fn repeat_parser(input: &str) -> IResult<&str, TermGenerator> {
+    match tuple((
+        int_parser,
+        char('x'),
+        delimited(
+            tuple((char('('), space0)),
+            expr_parser,
+            tuple((space0, char(')'))),
+        ),
+    ))(input)
+    {
+        Ok((input, (n, _, expr))) => Ok((input, TermGenerator::Repeat(n, Box::new(expr)))),
+        Err(e) => Err(e),
+    }
+}
+
+/// group_parser reads a parenthesized expression as a single term. A lone
+/// number in parentheses is left for the target low operator, `(3)`.
+fn group_parser(input: &str) -> IResult<&str, TermGenerator> {
+    match verify(
+        delimited(
+            tuple((char('('), space0)),
+            expr_parser,
+            tuple((space0, char(')'))),
+        ),
+        |expr: &ExprGenerator| {
+            expr.terms.len() > 1 || !matches!(expr.terms[0].term, TermGenerator::Constant(_))
+        },
+    )(input)
+    {
+        Ok((input, expr)) => Ok((input, TermGenerator::Group(Box::new(expr)))),
+        Err(e) => Err(e),
+    }
 }
 
+fn decimal_const_parser(input: &str) -> IResult<&str, TermGenerator> {
+    match preceded(space0, decimal_parser)(input) {
+        Ok((input, n)) => Ok((input, TermGenerator::Decimal(n))),
+        Err(e) => Err(e),
+    }
+}
+
+/// decimal_parser reads a number with one to three decimal places. A
+/// number too large to keep its thousandths in an `i32` is a parse error.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::decimal_parser;
+/// use dice_nom::results::Decimal;
+/// assert_eq!(decimal_parser("1.5"), Ok(("", Decimal(1500))));
+/// assert_eq!(decimal_parser("0.125"), Ok(("", Decimal(125))));
+/// assert!(decimal_parser("1.2345").is_err());
+/// assert!(decimal_parser("3").is_err());
+/// assert_eq!(decimal_parser("2147483.647"), Ok(("", Decimal(i32::MAX))));
+/// assert!(decimal_parser("2147483.648").is_err());
+///
+/// // a decimal too large is an error, not a smaller number
+/// assert!(dice_nom::parse("1d20 + 99999999.5").is_err());
+/// ```
+pub fn decimal_parser(input: &str) -> IResult<&str, Decimal> {
+    match separated_pair(
+        int_parser,
+        char('.'),
+        terminated(verify(digit1, |s: &str| s.len() <= 3), not(digit1)),
+    )(input)
+    {
+        Ok((rest, (whole, frac))) => {
+            let scale = 10i32.pow(3 - frac.len() as u32);
+            match whole
+                .checked_mul(Decimal::SCALE)
+                .and_then(|n| n.checked_add(frac.parse::<i32>().unwrap() * scale))
+            {
+                Some(n) => Ok((rest, Decimal(n))),
+                None => Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::TooLarge))),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// const_parser reads a whole number. A number right before a `d` is the
+/// count of a pool, so a count that is too large is an error rather than a
+/// constant followed by a die.
 fn const_parser(input: &str) -> IResult<&str, TermGenerator> {
-    match preceded(space0, digit1)(input) {
-        Ok((input, chars)) => Ok((
-            input,
-            TermGenerator::Constant(chars.parse::<i32>().unwrap()),
-        )),
+    match preceded(space0, terminated(int_parser, not(one_of("dD"))))(input) {
+        Ok((input, n)) => Ok((input, TermGenerator::Constant(n))),
         Err(e) => Err(e),
     }
 }
 
 fn pool_parser(input: &str) -> IResult<&str, TermGenerator> {
-    match tuple((opt(digit1), is_a("dD"), range_parser, opt(pool_op_parser)))(input) {
-        Ok((input, (count, _, range, op))) => {
-            let count = match count {
-                Some(chars) => chars.parse::<i32>().unwrap(),
-                None => 1,
-            };
+    match tuple((opt(count_parser), is_a("dD"), die_parser, pool_ops_parser))(input) {
+        Ok((input, (count, _, (range, die), ops))) => {
+            let count = count.unwrap_or(Count::Fixed(1));
             Ok((
                 input,
-                TermGenerator::Pool(PoolGenerator { count, range, op }),
+                TermGenerator::Pool(PoolGenerator { count, range, die, ops }),
             ))
         }
         Err(e) => Err(e),
     }
 }
 
+/// count_parser reads the number of dice in a pool, either a number or a
+/// parenthesized expression that is rolled for the count, e.g. `(1d4)d6`.
+fn count_parser(input: &str) -> IResult<&str, Count> {
+    alt((fixed_count_parser, rolled_count_parser))(input)
+}
+
+/// fixed_count_parser reads how many dice are rolled. A count too large
+/// for an `i32` is a parse error; any other count parses, and
+/// `Generator::generate_bounded` sets how many dice may be rolled.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::generators::GenError;
+/// let mut rng = rand::thread_rng();
+/// let gen = dice_nom::parse("2000d6").unwrap();
+/// assert_eq!(gen.generate(&mut rng).lhs.count(), 2000);
+/// assert_eq!(gen.generate_bounded(&mut rng, 1000).err(), Some(GenError::TooManyDice(1000)));
+/// assert!(gen.generate_bounded(&mut rng, 2000).is_ok());
+///
+/// assert!(dice_nom::parse("99999999999d6").is_err());
+/// assert!(dice_nom::parse("99999999999999999999d6 + 1").is_err());
+/// assert!(dice_nom::parse("99999999999x(1d6)").is_err());
+///
+/// // constants too large for an `i32` are errors as well
+/// assert!(dice_nom::parse("1d6 + 99999999999").is_err());
+/// assert!(dice_nom::parse("1d6 + 99999999999.5").is_err());
+/// ```
+fn fixed_count_parser(input: &str) -> IResult<&str, Count> {
+    match int_parser(input) {
+        Ok((input, n)) => Ok((input, Count::Fixed(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn rolled_count_parser(input: &str) -> IResult<&str, Count> {
+    match group_parser(input) {
+        Ok((input, term)) => Ok((input, Count::Rolled(Box::new(term)))),
+        Err(e) => Err(e),
+    }
+}
+
+fn die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    alt((
+        preset_die_parser,
+        weighted_die_parser,
+        standard_die_parser,
+        fudge_die_parser,
+        ranged_die_parser,
+        faced_die_parser,
+    ))(input)
+}
+
+/// preset_die_parser reads the names of the standard dice that are not
+/// numbered from 1: `coin`, `fudge`, `66` (two d6 read as tens and units)
+/// and `00` (percentile dice read as tens and units).
+fn preset_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    match terminated(alt((tag("coin"), tag("fudge"), tag("66"), tag("00"))), not(digit1))(input) {
+        Ok((input, "coin")) => Ok((input, (1, Die::Coin))),
+        Ok((input, "fudge")) => Ok((input, (1, Die::Fudge))),
+        Ok((input, "00")) => Ok((input, (100, Die::Percentile))),
+        Ok((input, _)) => Ok((input, (66, Die::D66))),
+        Err(e) => Err(e),
+    }
+}
+
+fn standard_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    // a die needs at least one face; `d0` is not a die
+    match verify(range_parser, |&range: &i32| range >= 1)(input) {
+        Ok((input, range)) => Ok((input, (range, Die::Standard))),
+        Err(e) => Err(e),
+    }
+}
+
+/// weighted_die_parser reads a standard die followed by the weight of each
+/// face, e.g. `6w[1,1,1,1,1,5]`.
+fn weighted_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    match tuple((
+        verify(range_parser, |&range: &i32| range >= 1),
+        char('w'),
+        delimited(
+            char('['),
+            separated_list1(
+                char(','),
+                delimited(space0, map_res(digit1, |chars: &str| chars.parse::<u32>()), space0),
+            ),
+            char(']'),
+        ),
+    ))(input)
+    {
+        Ok((input, (range, _, weights))) => Ok((input, (range, Die::Weighted(weights)))),
+        Err(e) => Err(e),
+    }
+}
+
+fn fudge_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    match is_a("fF")(input) {
+        Ok((input, _)) => Ok((input, (1, Die::Fudge))),
+        Err(e) => Err(e),
+    }
+}
+
+/// ranged_die_parser reads the lowest and highest face of a die numbered
+/// between them, e.g. `[0-9]`.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rand::thread_rng();
+/// let gen = dice_nom::parse("3d[0-9]").unwrap();
+/// assert_eq!(format!("{}", gen), "3d[0-9]");
+/// assert!(dice_nom::parse("d[5-1]").is_err());
+///
+/// // a d[0-5] can roll a 0
+/// let gen = dice_nom::parse("d[0-5]").unwrap();
+/// assert!((0..1000).any(|_| gen.generate(&mut rng).sum() == 0));
+/// assert!((0..1000).all(|_| (0..=5).contains(&gen.generate(&mut rng).sum())));
+///
+/// // explosions are on the highest face
+/// let gen = dice_nom::parse("d[-2-1]**").unwrap();
+/// assert_eq!(format!("{}", gen), "1d[-2-1]**");
+/// for _ in 0..100 {
+///     let pool = gen.generate(&mut rng).lhs;
+///     let values: Vec<i32> = pool.values.iter().map(|v| v.value).collect();
+///     assert_eq!(values.iter().filter(|&&v| v == 1).count(), values.len() - 1);
+/// }
+/// ```
+fn ranged_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    match verify(
+        delimited(char('['), separated_pair(face_parser, char('-'), face_parser), char(']')),
+        |&(low, high): &(i32, i32)| low <= high,
+    )(input)
+    {
+        Ok((input, (low, high))) => Ok((input, (high, Die::Ranged(low)))),
+        Err(e) => Err(e),
+    }
+}
+
+fn faced_die_parser(input: &str) -> IResult<&str, (i32, Die)> {
+    match faces_parser(input) {
+        Ok((input, faces)) => {
+            let range = *faces.iter().max().unwrap();
+            Ok((input, (range, Die::Faces(faces))))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// faces_parser reads an explicit list of die faces such as `[0,0,1,1,2,3]`.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::faces_parser;
+/// assert_eq!(faces_parser("[0,0,1,1,2,3]"), Ok(("", vec![0, 0, 1, 1, 2, 3])));
+/// assert_eq!(faces_parser("[-1, 5]+1"), Ok(("+1", vec![-1, 5])));
+/// assert!(faces_parser("[]").is_err());
+/// ```
+pub fn faces_parser(input: &str) -> IResult<&str, Vec<i32>> {
+    delimited(
+        char('['),
+        separated_list1(delimited(space0, char(','), space0), face_parser),
+        char(']'),
+    )(input)
+}
+
+fn face_parser(input: &str) -> IResult<&str, i32> {
+    match tuple((opt(char('-')), int_parser))(input) {
+        Ok((input, (sign, n))) => Ok((input, if sign.is_some() { -n } else { n })),
+        Err(e) => Err(e),
+    }
+}
+
+/// int_parser reads a whole number. A number too large for an `i32` is a
+/// parse error rather than a panic.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::int_parser;
+/// assert_eq!(int_parser("2147483647d"), Ok(("d", i32::MAX)));
+/// assert!(int_parser("2147483648").is_err());
+/// assert!(int_parser("d6").is_err());
+/// ```
+pub fn int_parser(input: &str) -> IResult<&str, i32> {
+    map_res(digit1, |chars: &str| chars.parse::<i32>())(input)
+}
+
 /// range_parser handles the special case of using `%` to mean 100.
 /// This is expanded to allow for any number of `%` to indicate a
-/// larger number (until the maximum value in `i32` is reached).
+/// larger number. A range too large for an `i32`, written either way,
+/// is a parse error.
 ///
 /// # Examples
 ///
@@ -258,34 +785,42 @@ fn pool_parser(input: &str) -> IResult<&str, TermGenerator> {
 /// assert_eq!(range_parser("%[12]"), Ok(("[12]", 100)));
 /// assert_eq!(range_parser("%%test"), Ok(("test", 1000)));
 /// assert_eq!(range_parser("%%%4567"), Ok(("4567", 10000)));
+/// assert_eq!(range_parser("%%%%%%%%"), Ok(("", 1_000_000_000)));
+/// assert!(range_parser("%%%%%%%%%").is_err());
+/// assert!(range_parser("99999999999999999999").is_err());
+///
+/// // a die too large is an error, not a panic
+/// assert!(dice_nom::parse("1d99999999999999999999").is_err());
+/// assert!(dice_nom::parse("1d[1-99999999999999999999]").is_err());
+/// assert!(dice_nom::parse("1d99999999999999999999w[1]").is_err());
 /// ```
 pub fn range_parser(input: &str) -> IResult<&str, i32> {
-    match alt((digit1, is_a("%")))(input) {
-        Ok((input, chars)) => {
-            if chars.starts_with('%') {
-                let base = 10i32;
-                let exp = chars.len() as u32;
-                let n = match base.checked_pow(exp) {
-                    Some(n) => 10 * n,
-                    None => 100,
-                };
-                Ok((input, n))
-            } else {
-                Ok((input, chars.parse::<i32>().unwrap()))
-            }
-        }
-        Err(e) => Err(e),
+    let percent: IResult<&str, &str> = is_a("%")(input);
+    match percent {
+        Ok((rest, chars)) => match 10i32.checked_pow(chars.len() as u32 + 1) {
+            Some(n) => Ok((rest, n)),
+            None => Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::TooLarge))),
+        },
+        Err(_) => int_parser(input),
     }
 }
 
 fn tgt_high_parser(input: &str) -> IResult<&str, TargetOp> {
-    match delimited(
-        tuple((space0, char('['), space0)),
-        digit1,
-        tuple((space0, char(']'))),
-    )(input)
+    match tuple((
+        delimited(
+            tuple((space0, char('['), space0)),
+            int_parser,
+            tuple((space0, char(']'))),
+        ),
+        opt(char('b')),
+    ))(input)
     {
-        Ok((input, chars)) => Ok((input, TargetOp::TargetHigh(chars.parse::<i32>().unwrap()))),
+        Ok((input, (n, botch))) => {
+            match botch {
+                Some(_) => Ok((input, TargetOp::TargetHighBotch(n))),
+                None => Ok((input, TargetOp::TargetHigh(n))),
+            }
+        }
         Err(e) => Err(e),
     }
 }
@@ -293,11 +828,11 @@ fn tgt_high_parser(input: &str) -> IResult<&str, TargetOp> {
 fn tgt_low_parser(input: &str) -> IResult<&str, TargetOp> {
     match delimited(
         tuple((space0, char('('), space0)),
-        digit1,
+        int_parser,
         tuple((space0, char(')'))),
     )(input)
     {
-        Ok((input, chars)) => Ok((input, TargetOp::TargetLow(chars.parse::<i32>().unwrap()))),
+        Ok((input, n)) => Ok((input, TargetOp::TargetLow(n))),
         Err(e) => Err(e),
     }
 }
@@ -311,8 +846,11 @@ fn tgt_low_parser(input: &str) -> IResult<&str, TargetOp> {
 /// use dice_nom::generators::TargetOp;
 /// assert_eq!(tgt_op_parser("[12]"), Ok(("", TargetOp::TargetHigh(12))));
 /// assert_eq!(tgt_op_parser("[ 12 ]"), Ok(("", TargetOp::TargetHigh(12))));
+/// assert_eq!(tgt_op_parser("[8]b"), Ok(("", TargetOp::TargetHighBotch(8))));
+/// assert!(tgt_op_parser("[99999999999]b").is_err());
 /// assert_eq!(tgt_op_parser("(12)"), Ok(("", TargetOp::TargetLow(12))));
 /// assert_eq!(tgt_op_parser("( 12 )"), Ok(("", TargetOp::TargetLow(12))));
+/// assert!(tgt_op_parser("(99999999999)").is_err());
 /// ```
 pub fn tgt_op_parser(input: &str) -> IResult<&str, TargetOp> {
     alt((tgt_high_parser, tgt_low_parser))(input)
@@ -327,15 +865,16 @@ pub fn tgt_op_parser(input: &str) -> IResult<&str, TargetOp> {
 /// use dice_nom::generators::SuccessOp;
 /// assert_eq!(succ_op_parser("{123}"), Ok(("", SuccessOp::TargetSucc(123))));
 /// assert_eq!(succ_op_parser("{ 123 }"), Ok(("", SuccessOp::TargetSucc(123))));
+/// assert!(succ_op_parser("{99999999999}").is_err());
 /// ```
 pub fn succ_op_parser(input: &str) -> IResult<&str, SuccessOp> {
     match delimited(
         tuple((space0, char('{'), space0)),
-        digit1,
+        int_parser,
         tuple((space0, char('}'))),
     )(input)
     {
-        Ok((input, chars)) => Ok((input, SuccessOp::TargetSucc(chars.parse::<i32>().unwrap()))),
+        Ok((input, n)) => Ok((input, SuccessOp::TargetSucc(n))),
         Err(e) => Err(e),
     }
 }
@@ -349,18 +888,75 @@ pub fn succ_op_parser(input: &str) -> IResult<&str, SuccessOp> {
 /// use dice_nom::generators::SuccessOp;
 /// assert_eq!(succ_next_op_parser("{123,45}"), Ok(("", SuccessOp::TargetSuccNext(123, 45))));
 /// assert_eq!(succ_next_op_parser("{ 123, 45 }"), Ok(("", SuccessOp::TargetSuccNext(123, 45))));
+/// assert!(succ_next_op_parser("{123, 99999999999}").is_err());
 /// ```
 pub fn succ_next_op_parser(input: &str) -> IResult<&str, SuccessOp> {
     match delimited(
         tuple((char('{'), space0)),
-        separated_pair(digit1, tuple((space0, char(','), space0)), digit1),
+        separated_pair(int_parser, tuple((space0, char(','), space0)), int_parser),
         tuple((space0, char('}'))),
     )(input)
     {
-        Ok((input, (n, m))) => Ok((
-            input,
-            SuccessOp::TargetSuccNext(n.parse::<i32>().unwrap(), m.parse::<i32>().unwrap()),
-        )),
+        Ok((input, (n, m))) => Ok((input, SuccessOp::TargetSuccNext(n, m))),
+        Err(e) => Err(e),
+    }
+}
+
+/// tiers_op_parser builds an operator that scores the number of
+/// thresholds the total meets. Three or more thresholds are listed like
+/// `{n, m}`; fewer need a trailing comma.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::tiers_op_parser;
+/// use dice_nom::generators::SuccessOp;
+/// assert_eq!(tiers_op_parser("{7,10,12}"), Ok(("", SuccessOp::Tiers(vec![7, 10, 12]))));
+/// assert_eq!(tiers_op_parser(" { 7, 10, }"), Ok(("", SuccessOp::Tiers(vec![7, 10]))));
+/// assert_eq!(tiers_op_parser("{7,}"), Ok(("", SuccessOp::Tiers(vec![7]))));
+/// assert!(tiers_op_parser("{7, 10}").is_err());
+/// ```
+pub fn tiers_op_parser(input: &str) -> IResult<&str, SuccessOp> {
+    match delimited(
+        tuple((space0, char('{'), space0)),
+        verify(
+            tuple((
+                separated_list1(tuple((space0, char(','), space0)), int_parser),
+                opt(tuple((space0, char(',')))),
+            )),
+            |(tiers, trailing): &(Vec<i32>, Option<_>)| tiers.len() >= 3 || trailing.is_some(),
+        ),
+        tuple((space0, char('}'))),
+    )(input)
+    {
+        Ok((input, (tiers, _))) => Ok((input, SuccessOp::Tiers(tiers))),
+        Err(e) => Err(e),
+    }
+}
+
+/// per_die_op_parser builds an operator that counts the successes of
+/// each die: one for every step, or with a second number one for the first
+/// step and one for every increment after.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::per_die_op_parser;
+/// use dice_nom::generators::SuccessOp;
+/// assert_eq!(per_die_op_parser("{{4}}"), Ok(("", SuccessOp::PerDie(4, None))));
+/// assert_eq!(per_die_op_parser(" {{ 4, 2 }}"), Ok(("", SuccessOp::PerDie(4, Some(2)))));
+/// assert!(per_die_op_parser("{4}").is_err());
+/// assert!(per_die_op_parser("{{99999999999}}").is_err());
+/// assert!(per_die_op_parser("{{4, 99999999999}}").is_err());
+/// ```
+pub fn per_die_op_parser(input: &str) -> IResult<&str, SuccessOp> {
+    match delimited(
+        tuple((space0, tag("{{"), space0)),
+        tuple((int_parser, opt(preceded(tuple((space0, char(','), space0)), int_parser)))),
+        tuple((space0, tag("}}"))),
+    )(input)
+    {
+        Ok((input, (n, m))) => Ok((input, SuccessOp::PerDie(n, m))),
         Err(e) => Err(e),
     }
 }
@@ -375,23 +971,172 @@ pub fn succ_next_op_parser(input: &str) -> IResult<&str, SuccessOp> {
 /// assert_eq!(pool_op_parser("!"), Ok(("", PoolOp::Explode(None))));
 /// assert_eq!(pool_op_parser(" ++ 3"), Ok(("", PoolOp::AddEach(Some(3)))));
 /// assert_eq!(pool_op_parser(" ADV"), Ok(("", PoolOp::Advantage)));
+/// assert_eq!(pool_op_parser("u3"), Ok(("", PoolOp::KeepUnique(3))));
+/// assert_eq!(pool_op_parser("x1"), Ok(("", PoolOp::DropValue(1))));
+/// assert_eq!(pool_op_parser("k[1,6]"), Ok(("", PoolOp::KeepValues(vec![1, 6]))));
+/// assert_eq!(pool_op_parser("!=[6]"), Ok(("", PoolOp::ExplodeOn(vec![6]))));
+/// assert_eq!(pool_op_parser("!5"), Ok(("", PoolOp::Explode(Some(5)))));
+/// assert_eq!(pool_op_parser("*t2"), Ok(("", PoolOp::ExplodeTop(Box::new(PoolOp::ExplodeEach(None)), 2))));
+/// assert_eq!(pool_op_parser("!^2"), Ok(("^2", PoolOp::Explode(None))));
+/// assert_eq!(pool_op_parser("!5^2"), Ok(("^2", PoolOp::Explode(Some(5)))));
+/// assert_eq!(
+///     pool_op_parser("!!:99999999999"),
+///     Ok(("", PoolOp::ExplosionLimit(Box::new(PoolOp::ExplodeUntil(None)), i32::MAX)))
+/// );
+/// assert_eq!(pool_op_parser("=m4"), Ok(("", PoolOp::CountMatches(4))));
+/// assert_eq!(pool_op_parser("min"), Ok(("", PoolOp::TakeMin)));
+/// assert_eq!(pool_op_parser(" max"), Ok(("", PoolOp::TakeMax)));
+/// assert_eq!(pool_op_parser("$"), Ok(("", PoolOp::Critical)));
+/// assert_eq!(pool_op_parser("^^1/2"), Ok(("", PoolOp::KeepBestPerGroup(2, 1))));
+/// assert_eq!(pool_op_parser("dl1"), Ok(("", PoolOp::DropLow(1))));
+/// assert_eq!(pool_op_parser("~2"), Ok(("", PoolOp::TakeMid(2))));
+/// assert_eq!(pool_op_parser("~`2"), Ok(("", PoolOp::TakeMidLow(2))));
+/// assert_eq!(pool_op_parser("S"), Ok(("", PoolOp::Sort(false))));
+/// assert_eq!(pool_op_parser(" dh2"), Ok(("", PoolOp::DropHigh(2))));
+/// assert_eq!(pool_op_parser("p"), Ok(("", PoolOp::Penetrate(None))));
+/// assert_eq!(pool_op_parser("o96"), Ok(("", PoolOp::OpenEnded(Some(96)))));
+/// assert_eq!(pool_op_parser("!c5"), Ok(("", PoolOp::Compound(Some(5)))));
+/// assert_eq!(pool_op_parser("b3k2"), Ok(("", PoolOp::BestOfRerolls(3, 2))));
+/// assert_eq!(pool_op_parser("r2"), Ok(("", PoolOp::RerollBelow(2))));
+/// assert_eq!(pool_op_parser("rr1"), Ok(("", PoolOp::RerollUntilAbove(1))));
+/// assert_eq!(pool_op_parser("L"), Ok(("", PoolOp::LuckyHigh)));
+/// assert_eq!(pool_op_parser("l"), Ok(("", PoolOp::LuckyLow)));
+/// assert_eq!(pool_op_parser("_3"), Ok(("", PoolOp::FloorEach(3))));
+/// assert_eq!(pool_op_parser("v6"), Ok(("", PoolOp::CapEach(6))));
+/// assert_eq!(pool_op_parser(" kh3"), Ok(("", PoolOp::TakeHigh(3))));
+/// assert_eq!(pool_op_parser(" kh50%"), Ok(("", PoolOp::TakeHighPercent(50))));
+/// assert_eq!(pool_op_parser("`25%"), Ok(("", PoolOp::TakeLowPercent(25))));
+///
+/// // a rolled explosion threshold must include at least one die
+/// let (input, op) = pool_op_parser("!(1d6)").unwrap();
+/// assert_eq!(input, "");
+/// assert_eq!(format!("{}", op), "!(1d6)");
+/// assert_eq!(pool_op_parser("!(3)"), Ok(("(3)", PoolOp::Explode(None))));
+///
+/// // a count too large for an `i32` is an error, not a panic
+/// assert!(dice_nom::parse("4d6dl99999999999").is_err());
+/// assert!(dice_nom::parse("4d6x99999999999").is_err());
+/// assert!(dice_nom::parse("6d6^^1/99999999999").is_err());
+/// assert!(dice_nom::parse("4d6u99999999999").is_err());
+/// assert!(dice_nom::parse("4d6=m99999999999").is_err());
+/// assert!(dice_nom::parse("4d6r99999999999").is_err());
+/// assert!(dice_nom::parse("4d6rr99999999999").is_err());
+/// assert!(dice_nom::parse("4d6_99999999999").is_err());
+/// assert!(dice_nom::parse("4d6v99999999999").is_err());
+/// assert!(dice_nom::parse("4d6 cap 99999999999").is_err());
+/// assert!(dice_nom::parse("4d6^99999999999%").is_err());
+/// assert!(dice_nom::parse("4d6 kl99999999999").is_err());
+/// assert!(dice_nom::parse("4d6~99999999999").is_err());
+/// assert!(dice_nom::parse("1d6b99999999999k1").is_err());
 /// ```
 pub fn pool_op_parser(input: &str) -> IResult<&str, PoolOp> {
     alt((
+        explode_ops_parser,
+        modify_ops_parser,
+        keep_ops_parser,
+        reroll_ops_parser,
+    ))(input)
+}
+
+/// explode_ops_parser reads an explosion operator, optionally followed by
+/// the most times each die may explode, e.g. `!!:3`.
+fn explode_ops_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((alt((explode_top_parser, explosion_parser)), opt(preceded(char(':'), digit1))))(input) {
+        Ok((input, (op, None))) => Ok((input, op)),
+        Ok((input, (op, Some(chars)))) => Ok((
+            input,
+            // any limit past the iteration limit is the same as no limit, so
+            // one too large for an `i32` is clamped rather than rejected
+            PoolOp::ExplosionLimit(Box::new(op), chars.parse::<i32>().unwrap_or(i32::MAX)),
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// explode_top_parser reads an explosion without a threshold followed by
+/// `t` and the number of top faces it explodes on, e.g. `*t2`.
+fn explode_top_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((
+        verify(explosion_parser, |op: &PoolOp| op.with_threshold(0).is_some()),
+        preceded(char('t'), int_parser),
+    ))(input)
+    {
+        Ok((input, (op, k))) => Ok((input, PoolOp::ExplodeTop(Box::new(op), k))),
+        Err(e) => Err(e),
+    }
+}
+
+fn explosion_parser(input: &str) -> IResult<&str, PoolOp> {
+    alt((
+        explode_on_op_parser,
         explode_until_op_parser,
+        compound_op_parser,
+        explode_rolled_op_parser,
         explode_op_parser,
         explode_each_until_op_parser,
         explode_each_op_parser,
+        penetrate_op_parser,
+        open_ended_op_parser,
+    ))(input)
+}
+
+fn modify_ops_parser(input: &str) -> IResult<&str, PoolOp> {
+    alt((
+        add_fraction_op_parser,
         add_op_parser,
         sub_op_parser,
+        floor_each_op_parser,
+        cap_each_op_parser,
+        critical_op_parser,
+        sort_op_parser,
+    ))(input)
+}
+
+fn keep_ops_parser(input: &str) -> IResult<&str, PoolOp> {
+    alt((
         take_mid_op_parser,
+        keep_best_per_group_op_parser,
         take_high_op_parser,
         take_low_op_parser,
+        drop_op_parser,
+        keep_unique_op_parser,
+        drop_value_op_parser,
+        keep_values_op_parser,
+        count_matches_op_parser,
+        cap_total_op_parser,
+        take_min_max_op_parser,
         command_op_parser,
     ))(input)
 }
 
-/// optional_num_parser wraps `digit1` to return an optional i32.
+fn reroll_ops_parser(input: &str) -> IResult<&str, PoolOp> {
+    alt((
+        best_of_rerolls_op_parser,
+        reroll_until_above_op_parser,
+        reroll_below_op_parser,
+        lucky_op_parser,
+    ))(input)
+}
+
+/// pool_ops_parser parses any number of pool operators. The operators
+/// are applied to the pool in the order they are written.
+///
+/// # Examples
+///
+/// ```
+/// use dice_nom::parsers::pool_ops_parser;
+/// use dice_nom::generators::PoolOp;
+/// assert_eq!(pool_ops_parser(""), Ok(("", vec![])));
+/// assert_eq!(pool_ops_parser("!! cap 30"), Ok(("", vec![
+///     PoolOp::ExplodeUntil(None),
+///     PoolOp::CapTotal(30),
+/// ])));
+/// ```
+pub fn pool_ops_parser(input: &str) -> IResult<&str, Vec<PoolOp>> {
+    many0(pool_op_parser)(input)
+}
+
+/// optional_num_parser wraps `int_parser` to return an optional i32.
 ///
 /// # Arguments
 ///
@@ -404,16 +1149,11 @@ pub fn pool_op_parser(input: &str) -> IResult<&str, PoolOp> {
 /// assert_eq!(optional_num_parser("test"), Ok(("test", None)));
 /// assert_eq!(optional_num_parser("123test"), Ok(("test", Some(123))));
 /// assert_eq!(optional_num_parser("  123test"), Ok(("test", Some(123))));
+/// assert!(dice_nom::parse("4d6 ++99999999999").is_err());
 /// ```
 pub fn optional_num_parser(input: &str) -> IResult<&str, Option<i32>> {
-    match tuple((space0, digit0))(input) {
-        Ok((input, (_, chars))) => {
-            if !chars.is_empty() {
-                Ok((input, Some(chars.parse::<i32>().unwrap())))
-            } else {
-                Ok((input, None))
-            }
-        }
+    match tuple((space0, opt(int_parser)))(input) {
+        Ok((input, (_, n))) => Ok((input, n)),
         Err(e) => Err(e),
     }
 }
@@ -425,6 +1165,37 @@ fn explode_op_parser(input: &str) -> IResult<&str, PoolOp> {
     }
 }
 
+fn explode_rolled_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    // a threshold of only constants is left for the target low parser
+    let rolled = |expr: &ExprGenerator| {
+        expr.terms
+            .iter()
+            .any(|t| matches!(t.term, TermGenerator::Pool(_)))
+    };
+    match preceded(
+        tag("!"),
+        delimited(char('('), verify(expr_parser, rolled), tuple((space0, char(')')))),
+    )(input)
+    {
+        Ok((input, expr)) => Ok((input, PoolOp::ExplodeRolled(Box::new(expr)))),
+        Err(e) => Err(e),
+    }
+}
+
+fn explode_on_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match preceded(tag("!="), faces_parser)(input) {
+        Ok((input, values)) => Ok((input, PoolOp::ExplodeOn(values))),
+        Err(e) => Err(e),
+    }
+}
+
+fn compound_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("!c"), optional_num_parser))(input) {
+        Ok((input, (_, num))) => Ok((input, PoolOp::Compound(num))),
+        Err(e) => Err(e),
+    }
+}
+
 fn explode_until_op_parser(input: &str) -> IResult<&str, PoolOp> {
     match tuple((tag("!!"), optional_num_parser))(input) {
         Ok((input, (_, num))) => Ok((input, PoolOp::ExplodeUntil(num))),
@@ -446,6 +1217,28 @@ fn explode_each_until_op_parser(input: &str) -> IResult<&str, PoolOp> {
     }
 }
 
+fn penetrate_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("p"), optional_num_parser))(input) {
+        Ok((input, (_, num))) => Ok((input, PoolOp::Penetrate(num))),
+        Err(e) => Err(e),
+    }
+}
+
+fn open_ended_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("o"), optional_num_parser))(input) {
+        Ok((input, (_, num))) => Ok((input, PoolOp::OpenEnded(num))),
+        Err(e) => Err(e),
+    }
+}
+
+fn add_fraction_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((space0, alt((tag("++"), tag("--"))), space0, decimal_parser))(input) {
+        Ok((input, (_, "--", _, n))) => Ok((input, PoolOp::AddFraction(Decimal(-n.0)))),
+        Ok((input, (_, _, _, n))) => Ok((input, PoolOp::AddFraction(n))),
+        Err(e) => Err(e),
+    }
+}
+
 fn add_op_parser(input: &str) -> IResult<&str, PoolOp> {
     match tuple((space0, tag("++"), space0, optional_num_parser))(input) {
         Ok((input, (_, _, _, num))) => Ok((input, PoolOp::AddEach(num))),
@@ -461,22 +1254,162 @@ fn sub_op_parser(input: &str) -> IResult<&str, PoolOp> {
 }
 
 fn take_mid_op_parser(input: &str) -> IResult<&str, PoolOp> {
-    match tuple((char('~'), digit1))(input) {
-        Ok((input, (_, chars))) => Ok((input, PoolOp::TakeMid(chars.parse::<i32>().unwrap()))),
+    match tuple((char('~'), opt(char('`')), int_parser))(input) {
+        Ok((input, (_, low, n))) => {
+            match low {
+                Some(_) => Ok((input, PoolOp::TakeMidLow(n))),
+                None => Ok((input, PoolOp::TakeMid(n))),
+            }
+        }
         Err(e) => Err(e),
     }
 }
 
 fn take_high_op_parser(input: &str) -> IResult<&str, PoolOp> {
-    match tuple((char('^'), digit1))(input) {
-        Ok((input, (_, chars))) => Ok((input, PoolOp::TakeHigh(chars.parse::<i32>().unwrap()))),
+    match tuple((
+        alt((tag("^"), preceded(space0, tag("kh")))),
+        int_parser,
+        opt(char('%')),
+    ))(input)
+    {
+        Ok((input, (_, n, pct))) => {
+            match pct {
+                Some(_) => Ok((input, PoolOp::TakeHighPercent(n))),
+                None => Ok((input, PoolOp::TakeHigh(n))),
+            }
+        }
         Err(e) => Err(e),
     }
 }
 
 fn take_low_op_parser(input: &str) -> IResult<&str, PoolOp> {
-    match tuple((char('`'), digit1))(input) {
-        Ok((input, (_, chars))) => Ok((input, PoolOp::TakeLow(chars.parse::<i32>().unwrap()))),
+    match tuple((
+        alt((tag("`"), preceded(space0, tag("kl")))),
+        int_parser,
+        opt(char('%')),
+    ))(input)
+    {
+        Ok((input, (_, n, pct))) => {
+            match pct {
+                Some(_) => Ok((input, PoolOp::TakeLowPercent(n))),
+                None => Ok((input, PoolOp::TakeLow(n))),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn drop_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((preceded(space0, char('d')), one_of("lh"), int_parser))(input) {
+        Ok((input, (_, 'l', n))) => Ok((input, PoolOp::DropLow(n))),
+        Ok((input, (_, _, n))) => Ok((input, PoolOp::DropHigh(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn keep_best_per_group_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("^^"), int_parser, char('/'), int_parser))(input) {
+        Ok((input, (_, keep, _, size))) => Ok((input, PoolOp::KeepBestPerGroup(size, keep))),
+        Err(e) => Err(e),
+    }
+}
+
+fn keep_unique_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('u'), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::KeepUnique(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn drop_value_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('x'), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::DropValue(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn keep_values_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match preceded(char('k'), faces_parser)(input) {
+        Ok((input, values)) => Ok((input, PoolOp::KeepValues(values))),
+        Err(e) => Err(e),
+    }
+}
+
+fn count_matches_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("=m"), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::CountMatches(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn sort_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match one_of("sS")(input) {
+        Ok((input, c)) => Ok((input, PoolOp::Sort(c == 's'))),
+        Err(e) => Err(e),
+    }
+}
+
+fn critical_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match char('$')(input) {
+        Ok((input, _)) => Ok((input, PoolOp::Critical)),
+        Err(e) => Err(e),
+    }
+}
+
+fn best_of_rerolls_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('b'), int_parser, char('k'), int_parser))(input) {
+        Ok((input, (_, rolls, _, keep))) => Ok((input, PoolOp::BestOfRerolls(rolls, keep))),
+        Err(e) => Err(e),
+    }
+}
+
+fn lucky_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match one_of("Ll")(input) {
+        Ok((input, 'L')) => Ok((input, PoolOp::LuckyHigh)),
+        Ok((input, _)) => Ok((input, PoolOp::LuckyLow)),
+        Err(e) => Err(e),
+    }
+}
+
+fn reroll_below_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('r'), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::RerollBelow(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn reroll_until_above_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((tag("rr"), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::RerollUntilAbove(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn floor_each_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('_'), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::FloorEach(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn cap_each_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match tuple((char('v'), int_parser))(input) {
+        Ok((input, (_, n))) => Ok((input, PoolOp::CapEach(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn cap_total_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match preceded(delimited(space0, tag("cap"), space0), int_parser)(input) {
+        Ok((input, n)) => Ok((input, PoolOp::CapTotal(n))),
+        Err(e) => Err(e),
+    }
+}
+
+fn take_min_max_op_parser(input: &str) -> IResult<&str, PoolOp> {
+    match preceded(space0, alt((tag("min"), tag("max"))))(input) {
+        Ok((input, "min")) => Ok((input, PoolOp::TakeMin)),
+        Ok((input, _)) => Ok((input, PoolOp::TakeMax)),
         Err(e) => Err(e),
     }
 }
@@ -494,24 +1427,28 @@ fn command_op_parser(input: &str) -> IResult<&str, PoolOp> {
 }
 
 fn comparison_op_parser(input: &str) -> IResult<&str, ComparisonOp> {
-    match alt((
-        tuple((delimited(space0, tag("<=>"), space0), succ_gen_parser)),
-        tuple((delimited(space0, tag(">="), space0), succ_gen_parser)),
-        tuple((delimited(space0, tag("<="), space0), succ_gen_parser)),
-        tuple((delimited(space0, tag(">"), space0), succ_gen_parser)),
-        tuple((delimited(space0, tag("<"), space0), succ_gen_parser)),
-        tuple((delimited(space0, tag("="), space0), succ_gen_parser)),
-    ))(input)
-    {
-        Ok((input, (tag, succ))) => match tag {
-            "<=>" => Ok((input, ComparisonOp::CMP(succ))),
-            ">=" => Ok((input, ComparisonOp::GE(succ))),
-            "<=" => Ok((input, ComparisonOp::LE(succ))),
-            ">" => Ok((input, ComparisonOp::GT(succ))),
-            "<" => Ok((input, ComparisonOp::LT(succ))),
-            "=" => Ok((input, ComparisonOp::EQ(succ))),
-            _ => panic!("unexpected tag"),
-        },
+    match tuple((comparison_tag_parser, succ_gen_parser))(input) {
+        Ok((input, (tag, succ))) => Ok((input, comparison_op(tag, succ))),
         Err(e) => Err(e),
     }
 }
+
+fn comparison_tag_parser(input: &str) -> IResult<&str, &str> {
+    delimited(
+        space0,
+        alt((tag("<=>"), tag(">="), tag("<="), tag(">"), tag("<"), tag("="))),
+        space0,
+    )(input)
+}
+
+fn comparison_op(tag: &str, succ: SuccGenerator) -> ComparisonOp {
+    match tag {
+        "<=>" => ComparisonOp::CMP(succ),
+        ">=" => ComparisonOp::GE(succ),
+        "<=" => ComparisonOp::LE(succ),
+        ">" => ComparisonOp::GT(succ),
+        "<" => ComparisonOp::LT(succ),
+        "=" => ComparisonOp::EQ(succ),
+        _ => panic!("unexpected tag"),
+    }
+}
@@ -1,7 +1,11 @@
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
+use std::cmp::Ordering;
 use std::fmt;
+use std::ops::Range;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Value {
     /// value of this roll (or constant) before modified
     pub value: i32,
@@ -12,6 +16,9 @@ pub struct Value {
     /// modifier to the value; value + add = sum if kept == true
     add: i32,
 
+    /// the part of `add` set by the `++` and `--` operators
+    each: i32,
+
     /// 1 by default; -1 if a "penalty" value
     mul: i32,
 
@@ -27,23 +34,78 @@ pub struct Value {
     /// true if this value matched a target operation
     hit: bool,
 
+    /// true if this value replaced an earlier roll of the same die
+    rerolled: bool,
+
+    /// the lowest value this roll can count as
+    floor: Option<i32>,
+
+    /// the highest value this roll can count as
+    cap: Option<i32>,
+
+    /// true if this is a fudge die (-1, 0 or 1)
+    fudge: bool,
+
+    /// true if this is a percentile roll of a tens die and a units die
+    percentile: bool,
+
+    /// true if this roll was marked as a critical (the highest face)
+    crit: bool,
+
     /// the current calculated value of this roll
     sum: i32,
+
+    /// thousandths added to the sum while kept, e.g. the `.5` of `++1.5`
+    fraction: i32,
 }
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.keep {
-            match self.bonus {
-                false => write!(f, "{}", self.sum),
-                true => write!(f, "{}*", self.sum),
+        // adjusted and discarded rolls show what was rolled, not what counted
+        if self.fudge && self.keep && !self.hit {
+            match self.sum {
+                n if n > 0 => write!(f, "+")?,
+                n if n < 0 => write!(f, "-")?,
+                _ => write!(f, "0")?,
             }
         } else {
-            match self.bonus {
-                false => write!(f, "{}-", self.value + self.add),
-                true => write!(f, "{}*-", self.value + self.add),
+            let shown = if self.keep && !self.is_floored() && !self.is_capped() {
+                self.sum
+            } else {
+                self.value + self.add
+            };
+
+            // an unmodified percentile roll shows both of its dice
+            match self.percentile_dice() {
+                Some((tens, units)) if shown == self.value => write!(f, "{:02}+{}", tens, units)?,
+                _ => write!(f, "{}", shown)?,
             }
         }
+
+        if self.crit {
+            write!(f, "!")?;
+        }
+
+        if self.is_floored() {
+            write!(f, "_")?;
+        }
+
+        if self.is_capped() {
+            write!(f, "v")?;
+        }
+
+        if self.bonus {
+            write!(f, "*")?;
+        }
+
+        if self.rerolled {
+            write!(f, "~")?;
+        }
+
+        if !self.keep {
+            write!(f, "-")?;
+        }
+        Ok(())
     }
 }
 
@@ -58,12 +120,39 @@ impl Value {
             bonus: false,
             keep: true,
             hit: false,
+            rerolled: false,
+            floor: None,
+            cap: None,
+            fudge: false,
+            percentile: false,
+            crit: false,
             sum: value,
+            fraction: 0,
+            each: 0,
         }
     }
 
     pub fn random<R: Rng + ?Sized>(range: i32, bonus: bool, rng: &mut R) -> Value {
         let value = rng.gen_range(0..range) + 1;
+        Value::random_with_value(value, range, bonus)
+    }
+
+    /// random_from rolls a die numbered from `low` to `range`. The range of
+    /// the value is still the highest face, so explosions happen on it.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut rng = rand::thread_rng();
+    /// for _ in 0..100 {
+    ///     let val = Value::random_from(0, 5, false, &mut rng);
+    ///     assert!((0..=5).contains(&val.value));
+    ///     assert_eq!(val.range, 5);
+    /// }
+    /// ```
+    pub fn random_from<R: Rng + ?Sized>(low: i32, range: i32, bonus: bool, rng: &mut R) -> Value {
+        let value = rng.gen_range(low..=range);
         Value {
             value,
             range,
@@ -73,7 +162,123 @@ impl Value {
             bonus,
             keep: true,
             hit: false,
+            rerolled: false,
+            floor: None,
+            cap: None,
+            fudge: false,
+            percentile: false,
+            crit: false,
             sum: value,
+            fraction: 0,
+            each: 0,
+        }
+    }
+
+    /// weighted rolls a die numbered 1 to `range` where the chance of each
+    /// face is its weight; `weights[0]` is the weight of a 1. Faces without
+    /// a weight never come up. If no face has any weight the roll is fair.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut rng = rand::thread_rng();
+    /// for _ in 0..100 {
+    ///     let val = Value::weighted(6, &[0, 0, 0, 0, 0, 1], false, &mut rng);
+    ///     assert_eq!(val.value, 6);
+    ///     assert_eq!(val.range, 6);
+    ///
+    ///     let val = Value::weighted(6, &[1, 0, 1], false, &mut rng);
+    ///     assert!(val.value == 1 || val.value == 3);
+    /// }
+    /// ```
+    pub fn weighted<R: Rng + ?Sized>(range: i32, weights: &[u32], bonus: bool, rng: &mut R) -> Value {
+        let weights = &weights[..weights.len().min(range.max(0) as usize)];
+        match WeightedIndex::new(weights) {
+            Ok(dist) => Value::random_with_value(dist.sample(rng) as i32 + 1, range, bonus),
+            Err(_) => Value::random(range, bonus, rng),
+        }
+    }
+
+    /// fudge rolls a Fudge/Fate die: -1, 0 or 1 with equal chance. These
+    /// are displayed as `-`, `0` and `+`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut rng = rand::thread_rng();
+    /// let val = Value::fudge(false, &mut rng);
+    /// assert!(val.sum() >= -1 && val.sum() <= 1);
+    /// assert_eq!(val.range, 1);
+    /// match val.sum() {
+    ///     1 => assert_eq!(format!("{}", val), "+"),
+    ///     0 => assert_eq!(format!("{}", val), "0"),
+    ///     _ => assert_eq!(format!("{}", val), "-"),
+    /// }
+    /// ```
+    pub fn fudge<R: Rng + ?Sized>(bonus: bool, rng: &mut R) -> Value {
+        let mut val = Value::random_with_value(rng.gen_range(-1..=1), 1, bonus);
+        val.fudge = true;
+        val
+    }
+
+    /// percentile rolls a tens die (00 to 90) and a units die (0 to 9) for
+    /// a value from 1 to 100; a 00 and a 0 are 100. An unmodified roll is
+    /// displayed as its two dice, e.g. `70+3`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut rng = rand::thread_rng();
+    /// let val = Value::percentile(false, &mut rng);
+    /// assert!(val.value >= 1 && val.value <= 100);
+    /// assert_eq!(val.range, 100);
+    /// let (tens, units) = val.percentile_dice().unwrap();
+    /// assert_eq!(format!("{}", val), format!("{:02}+{}", tens, units));
+    /// ```
+    pub fn percentile<R: Rng + ?Sized>(bonus: bool, rng: &mut R) -> Value {
+        let value = match rng.gen_range(0..10) * 10 + rng.gen_range(0..10) {
+            0 => 100,
+            n => n,
+        };
+        Value::percentile_with_value(value, bonus)
+    }
+
+    /// percentile_with_value is a percentile roll of `value` (1 to 100).
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let val = Value::percentile_with_value(73, false);
+    /// assert_eq!(val.percentile_dice(), Some((70, 3)));
+    /// assert_eq!(format!("{}", val), "70+3");
+    ///
+    /// // a 00 and a 0 are 100
+    /// let val = Value::percentile_with_value(100, false);
+    /// assert_eq!(val.percentile_dice(), Some((0, 0)));
+    /// assert_eq!(val.sum(), 100);
+    /// assert_eq!(format!("{}", val), "00+0");
+    ///
+    /// let val = Value::percentile_with_value(5, true);
+    /// assert_eq!(format!("{}", val), "00+5*");
+    /// assert_eq!(Value::random_with_value(73, 100, false).percentile_dice(), None);
+    /// ```
+    pub fn percentile_with_value(value: i32, bonus: bool) -> Value {
+        let mut val = Value::random_with_value(value, 100, bonus);
+        val.percentile = true;
+        val
+    }
+
+    /// percentile_dice is the tens die and the units die of a percentile
+    /// roll, e.g. `(70, 3)` for 73 and `(0, 0)` for 100.
+    pub fn percentile_dice(&self) -> Option<(i32, i32)> {
+        if self.percentile {
+            Some((self.value % 100 / 10 * 10, self.value % 10))
+        } else {
+            None
         }
     }
 
@@ -87,7 +292,15 @@ impl Value {
             bonus,
             keep: true,
             hit: false,
+            rerolled: false,
+            floor: None,
+            cap: None,
+            fudge: false,
+            percentile: false,
+            crit: false,
             sum: value,
+            fraction: 0,
+            each: 0,
         }
     }
 
@@ -111,6 +324,81 @@ impl Value {
         self.bonus
     }
 
+    pub fn is_fudge(&self) -> bool {
+        self.fudge
+    }
+
+    pub fn is_floored(&self) -> bool {
+        matches!(self.floor, Some(floor) if self.value < floor)
+    }
+
+    pub fn is_capped(&self) -> bool {
+        matches!(self.cap, Some(cap) if self.value > cap)
+    }
+
+    pub fn is_rerolled(&self) -> bool {
+        self.rerolled
+    }
+
+    /// is_penalty is true for a value that is subtracted from the total.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(4, 6, false);
+    /// val.set_modifier(1);
+    /// val.mark_penalty();
+    /// assert!(val.is_penalty());
+    /// assert_eq!(val.multiplier(), -1);
+    /// assert_eq!(val.base_value(), 4);
+    /// assert_eq!(val.modifier(), 1);
+    /// assert_eq!(val.range(), 6);
+    /// assert_eq!(val.sum(), -5);
+    ///
+    /// // discarded values count for nothing
+    /// val.mark_discarded();
+    /// assert_eq!(val.multiplier(), 0);
+    /// assert!(!val.is_penalty());
+    /// ```
+    pub fn is_penalty(&self) -> bool {
+        self.mul < 0
+    }
+
+    /// multiplier is 1 for an added value, -1 for a penalty and 0 once the
+    /// value is discarded.
+    pub fn multiplier(&self) -> i32 {
+        self.mul
+    }
+
+    /// base_value is the value rolled, before any modifier, floor or cap.
+    pub fn base_value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn range(&self) -> i32 {
+        self.range
+    }
+
+    pub fn is_crit(&self) -> bool {
+        self.crit
+    }
+
+    /// is_fumble is true for a die that rolled a natural 1. Constants and
+    /// fudge dice are never fumbles.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// assert!(Value::random_with_value(1, 20, false).is_fumble());
+    /// assert!(!Value::random_with_value(2, 20, false).is_fumble());
+    /// assert!(!Value::constant(1).is_fumble());
+    /// ```
+    pub fn is_fumble(&self) -> bool {
+        self.is_random() && !self.fudge && self.value == 1
+    }
+
     pub fn is_discarded(&self) -> bool {
         !self.keep
     }
@@ -122,17 +410,170 @@ impl Value {
     pub fn set_modifier(&mut self, add: i32) {
         self.add = add;
         if self.keep {
-            self.sum = self.mul * (self.value + add);
+            self.sum = self.mul * self.face().saturating_add(add);
         }
     }
 
+    /// fraction is the part of a fractional modifier below 1, in
+    /// thousandths. It counts towards the pool total but not the sum.
+    pub fn fraction(&self) -> i32 {
+        self.fraction
+    }
+
+    /// add_fraction adds a fractional modifier: the whole part is added to
+    /// the modifier and the rest is kept as the fraction.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::{Decimal, Value};
+    /// let mut val = Value::random_with_value(3, 6, false);
+    /// val.add_fraction(Decimal(1500));
+    /// assert_eq!(val.sum(), 4);
+    /// assert_eq!(val.fraction(), 500);
+    /// ```
+    pub fn add_fraction(&mut self, n: Decimal) {
+        self.set_modifier(self.add + n.whole());
+        self.fraction += n.fraction();
+    }
+
+    /// set_each sets the modifier of the `++` and `--` operators,
+    /// replacing any earlier one. What other operators added to the
+    /// modifier (compounded rolls, best-of rerolls, the penetration
+    /// penalty) is kept.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(6, 6, false);
+    /// val.set_modifier(-1);
+    /// val.set_each(1);
+    /// val.set_each(2);
+    /// assert_eq!(val.modifier(), 1);
+    /// assert_eq!(val.sum(), 7);
+    /// ```
+    pub fn set_each(&mut self, n: i32) {
+        let add = self.add.saturating_sub(self.each).saturating_add(n);
+        self.each = n;
+        self.fraction = 0;
+        self.set_modifier(add);
+    }
+
+    /// set_each_fraction is `set_each` for a fractional modifier, e.g.
+    /// `++1.5`; the part below 1 is kept as the fraction.
+    pub fn set_each_fraction(&mut self, n: Decimal) {
+        self.set_each(n.whole());
+        self.fraction = n.fraction();
+    }
+
     pub fn mark_bonus(&mut self) {
         self.bonus = true;
     }
 
+    /// set_floor raises the rolled value to `floor` when it is lower. The
+    /// rolled value is unchanged; only the sum is affected.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(1, 6, false);
+    /// val.set_floor(3);
+    /// assert_eq!(val.value, 1);
+    /// assert_eq!(val.sum(), 3);
+    /// assert!(val.is_floored());
+    /// assert_eq!(format!("{}", val), "1_");
+    ///
+    /// val.set_modifier(2);
+    /// assert_eq!(val.sum(), 5);
+    /// ```
+    pub fn set_floor(&mut self, floor: i32) {
+        self.floor = Some(floor);
+        if self.keep && !self.hit {
+            self.sum = self.mul * self.face().saturating_add(self.add);
+        }
+    }
+
+    /// set_cap lowers the rolled value to `cap` when it is higher. The
+    /// rolled value is unchanged; only the sum is affected.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(9, 10, false);
+    /// val.set_cap(6);
+    /// assert_eq!(val.value, 9);
+    /// assert_eq!(val.sum(), 6);
+    /// assert!(val.is_capped());
+    /// assert_eq!(format!("{}", val), "9v");
+    /// ```
+    pub fn set_cap(&mut self, cap: i32) {
+        self.cap = Some(cap);
+        if self.keep && !self.hit {
+            self.sum = self.mul * self.face().saturating_add(self.add);
+        }
+    }
+
+    fn face(&self) -> i32 {
+        let mut face = self.value;
+        if let Some(floor) = self.floor {
+            face = face.max(floor);
+        }
+        if let Some(cap) = self.cap {
+            face = face.min(cap);
+        }
+        face
+    }
+
+    pub fn mark_rerolled(&mut self) {
+        self.rerolled = true;
+    }
+
+    /// mark_crit flags the roll as a critical. The sum is unchanged and the
+    /// roll is displayed with a `!` after it.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(20, 20, false);
+    /// val.mark_crit();
+    /// assert!(val.is_crit());
+    /// assert_eq!(val.sum(), 20);
+    /// assert_eq!(format!("{}", val), "20!");
+    /// ```
+    pub fn mark_crit(&mut self) {
+        self.crit = true;
+    }
+
     pub fn mark_penalty(&mut self) {
         self.mul = -1;
-        self.sum = self.mul * (self.value + self.add);
+        self.sum = self.mul * self.face().saturating_add(self.add);
+    }
+
+    /// negate flips the sign of the value, so a value subtracted twice
+    /// (e.g. `10 - (6 - 1d4)`) counts as added again. A discarded value
+    /// still counts for nothing.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Value;
+    /// let mut val = Value::random_with_value(4, 6, false);
+    /// val.mark_penalty();
+    /// val.mark_penalty();
+    /// assert_eq!(val.sum(), -4);
+    /// val.negate();
+    /// assert_eq!(val.sum(), 4);
+    /// assert!(!val.is_penalty());
+    /// val.negate();
+    /// assert_eq!(val.sum(), -4);
+    /// ```
+    pub fn negate(&mut self) {
+        self.mul = -self.mul;
+        self.sum = self.mul * self.face().saturating_add(self.add);
     }
 
     pub fn mark_discarded(&mut self) {
@@ -160,16 +601,38 @@ impl Value {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pool {
     pub values: Vec<Value>,
-    value: Option<i32>
+    value: Option<i32>,
+    labels: Vec<(String, Range<usize>)>,
+    repeats: Vec<(Range<usize>, i32)>,
+    terms: Vec<(String, Range<usize>, i32)>,
 }
 
+/// Formatting a pool lists each value followed by the sum. The alternate
+/// form (`{:#}`) leaves out discarded values; they still count internally.
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::generators::PoolOp;
+/// use dice_nom::results::Pool;
+/// let mut pool = Pool::from_faces(&[(6, 6), (2, 6), (5, 6)]);
+/// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rand::thread_rng());
+/// assert_eq!(format!("{}", pool), "6, 5, 2- = 11");
+/// assert_eq!(format!("{:#}", pool), "6, 5 = 11");
+/// assert_eq!(pool.count(), 3);
+/// ```
 impl fmt::Display for Pool {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut first = true;
         for v in self.values.iter() {
+            if f.alternate() && v.is_discarded() {
+                continue;
+            }
+
             if first {
                 write!(f, "{}", v)?;
                 first = false;
@@ -180,6 +643,9 @@ impl fmt::Display for Pool {
 
         match self.value {
             Some(v) => write!(f, " = {} {{{}}}", self.sum(), v),
+            None if self.fraction() != 0 => {
+                write!(f, " = {} {{{}}}", self.sum_f64(), self.value())
+            }
             None => write!(f, " = {}", self.sum()),
         }
     }
@@ -196,6 +662,9 @@ impl Pool {
         Pool {
             values: vec![],
             value: None,
+            labels: vec![],
+            repeats: vec![],
+            terms: vec![],
         }
     }
 
@@ -203,20 +672,58 @@ impl Pool {
         Pool {
             values,
             value: None,
+            labels: vec![],
+            repeats: vec![],
+            terms: vec![],
         }
     }
 
-    pub fn range(&self) -> i32 {
-        if self.values.is_empty() {
-            0
-        } else {
-            self.values
+    /// from_faces builds a pool of rolled (non-constant) values from pairs
+    /// of `(value, range)`. This is mostly useful for building fixtures.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Pool;
+    /// let pool = Pool::from_faces(&[(3, 4), (5, 6), (7, 8)]);
+    /// assert_eq!(pool.count(), 3);
+    /// assert_eq!(pool.sum(), 15);
+    /// assert_eq!(pool.range(), 8);
+    /// assert!(pool.values.iter().all(|v| v.is_random()));
+    /// ```
+    pub fn from_faces(faces: &[(i32, i32)]) -> Pool {
+        Pool::new_with_values(
+            faces
                 .iter()
-                .filter(|&v| !v.constant)
-                .map(|&v| v.range)
-                .max()
-                .unwrap()
-        }
+                .map(|&(value, range)| Value::random_with_value(value, range, false))
+                .collect(),
+        )
+    }
+
+    /// range is the largest range of any die in the pool. A pool that is
+    /// empty or holds only constants has a range of 0.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::{Pool, Value};
+    /// let pool = Pool::new_with_values(vec![Value::constant(2), Value::constant(2)]);
+    /// assert_eq!(pool.range(), 0);
+    /// assert_eq!(pool.sum(), 4);
+    ///
+    /// let gen = dice_nom::parse("2 + 2").unwrap();
+    /// let results = gen.generate(&mut rand::thread_rng());
+    /// assert_eq!(format!("{}", gen), "2 + 2");
+    /// assert_eq!(results.lhs.range(), 0);
+    /// assert_eq!(results.sum(), 4);
+    /// ```
+    pub fn range(&self) -> i32 {
+        self.values
+            .iter()
+            .filter(|&v| !v.constant)
+            .map(|&v| v.range)
+            .max()
+            .unwrap_or(0)
     }
 
     pub fn count(&self) -> usize {
@@ -227,43 +734,388 @@ impl Pool {
         self.values.iter().map(|&v| v.sum()).sum()
     }
 
+    /// dice lists the state of every die rolled (constants are left out).
+    pub fn dice(&self) -> Vec<DieState> {
+        self.values
+            .iter()
+            .filter(|v| v.is_random())
+            .map(|v| DieState {
+                value: v.value,
+                sum: v.sum(),
+                kept: !v.is_discarded(),
+                bonus: v.is_bonus(),
+                hit: v.is_hit(),
+                penalty: v.is_penalty(),
+            })
+            .collect()
+    }
+
+    /// breakdown lists the dice between brackets, followed by any constants
+    /// and the value of the pool.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::{Pool, Value};
+    /// let mut pool = Pool::from_faces(&[(4, 6), (6, 6), (2, 6)]);
+    /// pool.values[1].mark_bonus();
+    /// pool.values.push(Value::constant(3));
+    /// assert_eq!(pool.breakdown(), "[4,6*,2] +3 = 15");
+    /// ```
+    pub fn breakdown(&self) -> String {
+        let dice: Vec<String> = self
+            .values
+            .iter()
+            .filter(|v| v.is_random())
+            .map(|v| v.to_string())
+            .collect();
+        let mut s = format!("[{}]", dice.join(","));
+        for v in self.values.iter().filter(|v| v.is_const()) {
+            s.push_str(&format!(" {:+}", v.sum()));
+        }
+        s.push_str(&format!(" = {}", self.value()));
+        s
+    }
+
+    /// append moves the values of `other` (and any labels on them) to the
+    /// end of this pool. The value of `other`, if set, and its terms are
+    /// not carried over.
+    pub fn append(&mut self, other: &mut Pool) {
+        let start = self.values.len();
+        for (label, range) in other.labels.drain(..) {
+            self.labels.push((label, range.start + start..range.end + start));
+        }
+        for (range, value) in other.repeats.drain(..) {
+            self.repeats.push((range.start + start..range.end + start, value));
+        }
+        self.values.append(&mut other.values);
+    }
+
+    /// push_label tags the values in `range` with a label.
+    pub fn push_label(&mut self, label: &str, range: Range<usize>) {
+        self.labels.push((String::from(label), range));
+    }
+
+    /// labeled lists each label with the values it tags, in the order the
+    /// labeled terms appear.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let gen = dice_nom::parse("2d6#slashing + 1d8#fire + 3").unwrap();
+    /// let pool = gen.generate(&mut rng).lhs;
+    /// let labeled = pool.labeled();
+    /// assert_eq!(labeled.len(), 2);
+    /// assert_eq!(labeled[0].0, "slashing");
+    /// assert_eq!(labeled[0].1.len(), 2);
+    /// assert_eq!(labeled[1].0, "fire");
+    /// assert_eq!(labeled[1].1[0].range, 8);
+    /// ```
+    pub fn labeled(&self) -> Vec<(&str, &[Value])> {
+        self.labels
+            .iter()
+            .map(|(label, range)| (label.as_str(), &self.values[range.clone()]))
+            .collect()
+    }
+
+    /// push_repeat records the values in `range` as one roll of a repeated
+    /// expression with the given value.
+    pub fn push_repeat(&mut self, range: Range<usize>, value: i32) {
+        self.repeats.push((range, value));
+    }
+
+    /// repeats lists the values and value of each roll of a repeated
+    /// expression, e.g. each attack in `3x(1d20 + 5)`.
+    pub fn repeats(&self) -> Vec<(&[Value], i32)> {
+        self.repeats
+            .iter()
+            .map(|(range, value)| (&self.values[range.clone()], *value))
+            .collect()
+    }
+
+    /// push_term records the values in `range` as a term of the expression
+    /// that was rolled, written as `text`, with the value it added.
+    pub fn push_term(&mut self, text: &str, range: Range<usize>, value: i32) {
+        self.terms.push((String::from(text), range, value));
+    }
+
+    /// terms lists the text, values and value of each term of the rolled
+    /// expression, e.g. `2d6!` and `+ 3` for `2d6! + 3`. The terms of a
+    /// group or repeated expression are not listed on their own.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let pool = dice_nom::parse("2d6 - 1d1 (1d4 + 1)").unwrap().generate(&mut rng).lhs;
+    /// let terms = pool.terms();
+    /// assert_eq!(terms.len(), 3);
+    /// assert_eq!(terms[0].0, "2d6");
+    /// assert_eq!(terms[0].1.len(), 2);
+    /// assert_eq!(terms[1], ("- 1d1", &pool.values[2..3], -1));
+    /// assert_eq!(terms[2].0, "(1d4 + 1)");
+    /// assert_eq!(terms[2].1.len(), 2);
+    /// ```
+    pub fn terms(&self) -> Vec<(&str, &[Value], i32)> {
+        self.terms
+            .iter()
+            .map(|(text, range, value)| (text.as_str(), &self.values[range.clone()], *value))
+            .collect()
+    }
+
+    /// label_breakdown lists the values of each label, e.g.
+    /// `slashing: [4,6], fire: [5]`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Pool;
+    /// let mut pool = Pool::from_faces(&[(4, 6), (6, 6), (5, 8)]);
+    /// pool.push_label("slashing", 0..2);
+    /// pool.push_label("fire", 2..3);
+    /// assert_eq!(pool.label_breakdown(), "slashing: [4,6], fire: [5]");
+    /// ```
+    pub fn label_breakdown(&self) -> String {
+        let groups: Vec<String> = self
+            .labeled()
+            .iter()
+            .map(|(label, values)| {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                format!("{}: [{}]", label, values.join(","))
+            })
+            .collect();
+        groups.join(", ")
+    }
+
     pub fn kept(&self) -> usize {
-        self.values.iter().filter(|&v| !v.is_discarded()).count()
+        self.kept_values().count()
     }
 
     pub fn hits(&self) -> usize {
-        self.values.iter().filter(|&v| v.is_hit()).count()
+        self.hit_values().count()
+    }
+
+    pub fn crits(&self) -> usize {
+        self.values.iter().filter(|&v| v.is_crit()).count()
+    }
+
+    pub fn fumbles(&self) -> usize {
+        self.values.iter().filter(|&v| v.is_fumble()).count()
     }
 
     pub fn bonus(&self) -> usize {
-        self.values.iter().filter(|&v| v.is_bonus()).count()
+        self.bonus_values().count()
+    }
+
+    /// kept_values iterates over the values that were not discarded.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::generators::PoolOp;
+    /// use dice_nom::results::Pool;
+    /// let mut pool = Pool::from_faces(&[(3, 6), (6, 6), (1, 6), (5, 6)]);
+    /// PoolOp::TakeHigh(2).apply_all(&mut pool, &mut rand::thread_rng());
+    /// let kept: Vec<i32> = pool.kept_values().map(|v| v.value).collect();
+    /// assert_eq!(kept, vec![6, 5]);
+    /// assert_eq!(pool.kept_values().map(|v| v.sum()).sum::<i32>(), pool.sum());
+    ///
+    /// pool.values[0].mark_bonus();
+    /// pool.values[1].mark_hit();
+    /// assert_eq!(pool.bonus_values().map(|v| v.value).collect::<Vec<i32>>(), vec![6]);
+    /// assert_eq!(pool.hit_values().map(|v| v.value).collect::<Vec<i32>>(), vec![5]);
+    /// ```
+    pub fn kept_values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter().filter(|v| !v.is_discarded())
+    }
+
+    /// bonus_values iterates over the values added by a bonus operator,
+    /// e.g. the extra dice of an explosion.
+    pub fn bonus_values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter().filter(|v| v.is_bonus())
     }
 
+    /// hit_values iterates over the values that matched a target.
+    pub fn hit_values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter().filter(|v| v.is_hit())
+    }
+
+    /// value is the value set by an operator or else the sum of the pool,
+    /// with any fractional modifiers rounded to the nearest whole number.
     pub fn value(&self) -> i32 {
+        self.value_rounded(RoundingMode::Nearest)
+    }
+
+    /// value_rounded is `value` with fractional modifiers rounded by `mode`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::RoundingMode;
+    /// let gen = dice_nom::parse("3d1++0.5").unwrap();
+    /// let pool = gen.generate(&mut rand::thread_rng()).lhs;
+    /// assert_eq!(pool.sum(), 3);
+    /// assert_eq!(pool.sum_f64(), 4.5);
+    /// assert_eq!(pool.value(), 5);
+    /// assert_eq!(pool.value_rounded(RoundingMode::Down), 4);
+    /// assert_eq!(pool.value_rounded(RoundingMode::Up), 5);
+    /// assert_eq!(format!("{}", pool), "1, 1, 1 = 4.5 {5}");
+    ///
+    /// // half a point on each of two dice is one more point
+    /// let gen = dice_nom::parse("2d6++0.5").unwrap();
+    /// assert_eq!(format!("{}", gen), "2d6++0.5");
+    /// let pool = gen.generate(&mut rand::thread_rng()).lhs;
+    /// assert_eq!(pool.value(), pool.sum() + 1);
+    /// ```
+    pub fn value_rounded(&self, mode: RoundingMode) -> i32 {
         if let Some(v) = self.value {
             v
-        } else {
+        } else if self.fraction() == 0 {
             self.sum()
+        } else {
+            mode.round(self.exact(), Decimal::SCALE as i64)
+        }
+    }
+
+    /// sum_f64 is `value` before any fractional modifiers are rounded.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("1d1 + 1.25 - 1d1--0.5").unwrap();
+    /// let pool = gen.generate(&mut rand::thread_rng()).lhs;
+    /// assert_eq!(pool.sum_f64(), 1.75);
+    /// assert_eq!(pool.value(), 2);
+    /// ```
+    pub fn sum_f64(&self) -> f64 {
+        match self.value {
+            Some(v) => v as f64,
+            None => self.exact() as f64 / Decimal::SCALE as f64,
         }
     }
 
+    /// fraction is the total of the fractional modifiers of the kept
+    /// values, in thousandths.
+    pub fn fraction(&self) -> i32 {
+        self.values
+            .iter()
+            .filter(|v| !v.hit)
+            .map(|v| v.fraction * v.mul)
+            .sum()
+    }
+
+    /// exact is the sum with the fractional modifiers, in thousandths.
+    fn exact(&self) -> i64 {
+        self.sum() as i64 * Decimal::SCALE as i64 + self.fraction() as i64
+    }
+
     pub fn set_value(&mut self, value: i32) {
         self.value = Some(value)
     }
+
+    /// has_value is true when the value of the pool was set by an operator
+    /// and is no longer its sum.
+    pub fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// sort_values_asc reorders the values from lowest to highest rolled
+    /// value. The sort is stable and the flags on each value are untouched.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::{Pool, Value};
+    /// let mut pool = Pool::new_with_values(vec![
+    ///     Value::random_with_value(4, 6, false),
+    ///     Value::random_with_value(1, 6, false),
+    ///     Value::random_with_value(6, 6, true),
+    /// ]);
+    /// pool.sort_values_asc();
+    /// let values: Vec<i32> = pool.values.iter().map(|v| v.value).collect();
+    /// assert_eq!(values, vec![1, 4, 6]);
+    /// assert!(pool.values[2].is_bonus());
+    /// assert_eq!(pool.sum(), 11);
+    /// ```
+    pub fn sort_values_asc(&mut self) {
+        self.values.sort_by_key(|v| v.value);
+    }
+
+    /// sort_values_desc reorders the values from highest to lowest rolled
+    /// value. The sort is stable and the flags on each value are untouched.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::{Pool, Value};
+    /// let mut pool = Pool::new_with_values(vec![
+    ///     Value::random_with_value(4, 6, false),
+    ///     Value::random_with_value(1, 6, false),
+    ///     Value::random_with_value(6, 6, true),
+    /// ]);
+    /// pool.sort_values_desc();
+    /// let values: Vec<i32> = pool.values.iter().map(|v| v.value).collect();
+    /// assert_eq!(values, vec![6, 4, 1]);
+    /// assert!(pool.values[0].is_bonus());
+    /// assert_eq!(pool.sum(), 11);
+    /// ```
+    pub fn sort_values_desc(&mut self) {
+        self.values.sort_by_key(|v| std::cmp::Reverse(v.value));
+    }
 }
 
+/// Results are the rolls of a generator: the left hand side, the right
+/// hand side of a comparison if there is one, and the comparison's result.
+/// Results are equal when every die and value is the same, so they can be
+/// used as keys, e.g. to count identical rolls.
+///
+/// * Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use rand::prelude::*;
+/// use rand::rngs::StdRng;
+/// let gen = dice_nom::parse("4d6^3 + 2 > 1d20").unwrap();
+/// let mut seen = HashSet::new();
+/// seen.insert(gen.generate(&mut StdRng::seed_from_u64(7)));
+/// seen.insert(gen.generate(&mut StdRng::seed_from_u64(7)));
+/// assert_eq!(seen.len(), 1);
+///
+/// let gen = dice_nom::parse("3 + 4").unwrap();
+/// assert_eq!(gen.generate(&mut rand::thread_rng()), gen.generate(&mut rand::thread_rng()));
+///
+/// // a copy keeps the roll as it was
+/// let mut results = dice_nom::parse("3d6").unwrap().generate(&mut rand::thread_rng());
+/// let saved = results.clone();
+/// results.lhs.values[0].mark_discarded();
+/// assert_ne!(results, saved);
+/// assert_eq!(saved.lhs.kept(), 3);
+/// assert_eq!(results.lhs.kept(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Results {
     pub lhs: Pool,
     pub rhs: Option<Pool>,
     pub value: i32,
+
+    /// the comparison between the two sides, if there is one
+    pub op: Option<Comparison>,
 }
 
 impl fmt::Display for Results {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.lhs)?;
-        match &self.rhs {
-            Some(rhs) => write!(f, " <> {} = {}", rhs, self.sum())?,
-            None => (),
+        if f.alternate() {
+            write!(f, "{:#}", self.lhs)?;
+            if let Some(rhs) = &self.rhs {
+                write!(f, " <> {:#} = {}", rhs, self.sum())?;
+            }
+        } else {
+            write!(f, "{}", self.lhs)?;
+            if let Some(rhs) = &self.rhs {
+                write!(f, " <> {} = {}", rhs, self.sum())?;
+            }
         }
         write!(f, "")
     }
@@ -276,4 +1128,447 @@ impl Results {
             None => self.lhs.value(),
         }
     }
+
+    /// margin is how far the left hand side beat the right hand side of a
+    /// comparison (negative when it fell short), the difference of an
+    /// opposed roll. There is no margin without a comparison.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let results = dice_nom::parse("10 > 3").unwrap().generate(&mut rng);
+    /// assert_eq!(results.sum(), 1);
+    /// assert_eq!(results.margin(), Some(7));
+    ///
+    /// let results = dice_nom::parse("2 <=> 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.sum(), -1);
+    /// assert_eq!(results.margin(), Some(-3));
+    ///
+    /// let results = dice_nom::parse("10 <=> 4").unwrap().generate(&mut rng);
+    /// assert_eq!(results.margin(), Some(6));
+    ///
+    /// let results = dice_nom::parse("3d8").unwrap().generate(&mut rng);
+    /// assert_eq!(results.margin(), None);
+    /// ```
+    pub fn margin(&self) -> Option<i32> {
+        self.rhs.as_ref().map(|rhs| self.lhs.value().saturating_sub(rhs.value()))
+    }
+
+    /// delta is another name for `margin`, the signed difference of an
+    /// opposed roll.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let results = dice_nom::parse("10 <=> 4").unwrap().generate(&mut rng);
+    /// assert_eq!(results.delta(), Some(6));
+    /// assert_eq!(results.delta(), results.margin());
+    /// ```
+    pub fn delta(&self) -> Option<i32> {
+        self.margin()
+    }
+
+    /// outcome is how the left hand side compares to the right hand side,
+    /// the `-1`, `0` or `1` of `<=>` as an `Ordering`; `margin` is by how
+    /// much. There is no outcome without a comparison.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// let mut rng = rand::thread_rng();
+    /// let results = dice_nom::parse("3 <=> 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.outcome(), Some(Ordering::Less));
+    ///
+    /// let results = dice_nom::parse("5 <=> 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.outcome(), Some(Ordering::Equal));
+    ///
+    /// // any comparison has an outcome
+    /// let results = dice_nom::parse("6 >= 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.outcome(), Some(Ordering::Greater));
+    ///
+    /// assert_eq!(dice_nom::parse("3d6").unwrap().generate(&mut rng).outcome(), None);
+    /// ```
+    pub fn outcome(&self) -> Option<Ordering> {
+        self.rhs.as_ref().map(|rhs| self.lhs.value().cmp(&rhs.value()))
+    }
+
+    /// succeeded is whether a `>`, `>=`, `<`, `<=` or `=` comparison
+    /// passed. `<=>` has three outcomes rather than two, so it has no
+    /// success (use `outcome`), and neither does a roll without a
+    /// comparison.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let mut rng = rand::thread_rng();
+    /// let results = dice_nom::parse("3 < 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.succeeded(), Some(true));
+    ///
+    /// let results = dice_nom::parse("3 = 5").unwrap().generate(&mut rng);
+    /// assert_eq!(results.succeeded(), Some(false));
+    ///
+    /// assert_eq!(dice_nom::parse("3 <=> 5").unwrap().generate(&mut rng).succeeded(), None);
+    /// assert_eq!(dice_nom::parse("3d6").unwrap().generate(&mut rng).succeeded(), None);
+    /// ```
+    pub fn succeeded(&self) -> Option<bool> {
+        match self.op? {
+            Comparison::CMP => None,
+            _ => Some(self.value == 1),
+        }
+    }
+
+    /// breakdown is a plain description of the roll for logs: the dice and
+    /// constants of each side and the total.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use rand::prelude::*;
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("3d6 + 3").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(42));
+    /// let dice: Vec<String> = results.dice().iter().map(|d| d.value.to_string()).collect();
+    /// assert_eq!(
+    ///     results.breakdown(),
+    ///     format!("[{}] +3 = {}", dice.join(","), results.sum())
+    /// );
+    ///
+    /// let gen = dice_nom::parse("1d20 + 2 >= 1d20 - 1").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(42));
+    /// assert!(results.breakdown().contains(" <> "));
+    /// assert_eq!(results.dice().len(), 2);
+    /// ```
+    pub fn breakdown(&self) -> String {
+        match &self.rhs {
+            Some(rhs) => format!("{} <> {} = {}", self.lhs.breakdown(), rhs.breakdown(), self.sum()),
+            None => self.lhs.breakdown(),
+        }
+    }
+
+    /// pretty is a longer rendering of the roll than `Display`: each term
+    /// on its own line with its dice and the value it added, then the
+    /// total. The sides of a comparison are indented under `left` and
+    /// `right`. Dice are marked the same way as in `Display` and the last
+    /// line explains the marks that were used.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use rand::prelude::*;
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("2d6! + 3 > 10").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(16));
+    /// assert_eq!(results.pretty(), "\
+    /// left
+    ///   2d6!: 6, 6, 3*, 2* = 17
+    ///   + 3 = 3
+    ///   = 20
+    /// right
+    ///   10 = 10
+    ///   = 10
+    /// total 1
+    /// marks: * bonus
+    /// ");
+    ///
+    /// let gen = dice_nom::parse("4d6^3 - 1").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(0));
+    /// assert_eq!(results.pretty(), "\
+    /// 4d6^3: 5, 5, 4, 1- = 14
+    /// - 1 = -1
+    /// total 13
+    /// marks: - discarded
+    /// ");
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut s = String::new();
+        match &self.rhs {
+            Some(rhs) => {
+                for (side, pool) in [("left", &self.lhs), ("right", rhs)] {
+                    s.push_str(side);
+                    s.push('\n');
+                    pretty_terms(pool, "  ", &mut s);
+                    s.push_str(&format!("  = {}\n", pool.value()));
+                }
+            }
+            None => pretty_terms(&self.lhs, "", &mut s),
+        }
+        s.push_str(&format!("total {}\n", self.sum()));
+
+        let mut values = self.lhs.values.iter().collect::<Vec<&Value>>();
+        if let Some(rhs) = &self.rhs {
+            values.extend(rhs.values.iter());
+        }
+        let marks: Vec<&str> = [
+            (values.iter().any(|v| v.is_crit()), "! crit"),
+            (values.iter().any(|v| v.is_floored()), "_ floored"),
+            (values.iter().any(|v| v.is_capped()), "v capped"),
+            (values.iter().any(|v| v.is_bonus()), "* bonus"),
+            (values.iter().any(|v| v.is_rerolled()), "~ rerolled"),
+            (values.iter().any(|v| v.is_discarded()), "- discarded"),
+        ]
+        .iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, mark)| *mark)
+        .collect();
+        if !marks.is_empty() {
+            s.push_str(&format!("marks: {}\n", marks.join(", ")));
+        }
+        s
+    }
+
+    /// to_vtt writes the roll as an inline roll for importing into a
+    /// virtual tabletop log. Each side is wrapped in `[[ ]]` and lists its
+    /// terms, each followed by its dice in brackets, then its value. Dice
+    /// are marked the same way as in `Display` (e.g. `[1-]` for a discarded
+    /// die); constants are left out of the brackets since they are already
+    /// written in the term. The two sides of a comparison are joined with
+    /// `<>` and followed by the result.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use rand::prelude::*;
+    /// use rand::rngs::StdRng;
+    /// let gen = dice_nom::parse("2d6! + 3 > 10").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(16));
+    /// assert_eq!(results.to_vtt(), "[[2d6![6][6][3*][2*] + 3 = 20]] <> [[10 = 10]] = 1");
+    ///
+    /// let gen = dice_nom::parse("4d6^3 - 1").unwrap();
+    /// let results = gen.generate(&mut StdRng::seed_from_u64(0));
+    /// assert_eq!(results.to_vtt(), "[[4d6^3[5][5][4][1-] - 1 = 13]]");
+    /// ```
+    pub fn to_vtt(&self) -> String {
+        match &self.rhs {
+            Some(rhs) => format!("{} <> {} = {}", vtt_roll(&self.lhs), vtt_roll(rhs), self.sum()),
+            None => vtt_roll(&self.lhs),
+        }
+    }
+
+    /// labeled lists the labeled values of both sides.
+    pub fn labeled(&self) -> Vec<(&str, &[Value])> {
+        let mut labeled = self.lhs.labeled();
+        if let Some(rhs) = &self.rhs {
+            labeled.append(&mut rhs.labeled());
+        }
+        labeled
+    }
+
+    /// dice lists the state of every die rolled on both sides.
+    pub fn dice(&self) -> Vec<DieState> {
+        let mut dice = self.lhs.dice();
+        if let Some(rhs) = &self.rhs {
+            dice.append(&mut rhs.dice());
+        }
+        dice
+    }
+}
+
+/// pretty_terms writes a line for each term of the pool: its text, its
+/// dice (left out when the term is only constants) and the value it added.
+/// A pool without terms is written as a single line.
+fn pretty_terms(pool: &Pool, indent: &str, s: &mut String) {
+    let mut terms = pool.terms();
+    if terms.is_empty() {
+        terms.push(("dice", &pool.values[..], pool.value()));
+    }
+    for (text, values, value) in terms {
+        if values.iter().all(|v| v.is_const()) {
+            s.push_str(&format!("{}{} = {}\n", indent, text, value));
+        } else {
+            let dice: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            s.push_str(&format!("{}{}: {} = {}\n", indent, text, dice.join(", "), value));
+        }
+    }
+}
+
+/// vtt_roll writes a pool as an inline roll, e.g. `[[2d6[4][2] + 3 = 9]]`.
+/// A pool without terms lists its dice after `dice`.
+fn vtt_roll(pool: &Pool) -> String {
+    let mut terms = pool.terms();
+    if terms.is_empty() {
+        terms.push(("dice", &pool.values[..], pool.value()));
+    }
+    let mut s = String::from("[[");
+    for (idx, (text, values, _)) in terms.iter().enumerate() {
+        if idx > 0 {
+            s.push(' ');
+        }
+        s.push_str(text);
+        for v in values.iter().filter(|v| v.is_random()) {
+            s.push_str(&format!("[{}]", v));
+        }
+    }
+    s.push_str(&format!(" = {}]]", pool.value()));
+    s
+}
+
+/// Comparison is the kind of comparison between the two sides of a roll,
+/// one for each `ComparisonOp`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Comparison {
+    GT,
+    GE,
+    LT,
+    LE,
+    EQ,
+    CMP,
+}
+
+impl Comparison {
+    /// compare is the value of a roll with this comparison: 1 when it
+    /// passes and 0 when it does not, or -1, 0 or 1 for `CMP`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::Comparison;
+    /// assert_eq!(Comparison::GE.compare(5, 5), 1);
+    /// assert_eq!(Comparison::LT.compare(5, 5), 0);
+    /// assert_eq!(Comparison::CMP.compare(3, 5), -1);
+    /// ```
+    pub fn compare(&self, lhs: i32, rhs: i32) -> i32 {
+        match self {
+            Comparison::GT => (lhs > rhs) as i32,
+            Comparison::GE => (lhs >= rhs) as i32,
+            Comparison::LT => (lhs < rhs) as i32,
+            Comparison::LE => (lhs <= rhs) as i32,
+            Comparison::EQ => (lhs == rhs) as i32,
+            Comparison::CMP => match lhs.cmp(&rhs) {
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+                Ordering::Greater => 1,
+            },
+        }
+    }
+}
+
+/// ConditionResults are the rolls of every generator in a condition and
+/// whether the condition passed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConditionResults {
+    pub passed: bool,
+    pub results: Vec<Results>,
+}
+
+impl fmt::Display for ConditionResults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, results) in self.results.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", results)?;
+        }
+        write!(f, " => {}", self.sum())
+    }
+}
+
+impl ConditionResults {
+    /// sum is 1 when the condition passed and 0 when it did not.
+    pub fn sum(&self) -> i32 {
+        self.passed as i32
+    }
+}
+
+/// DieState is a read-only summary of a single die for custom rendering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DieState {
+    /// the rolled value
+    pub value: i32,
+
+    /// what the die counts toward the total
+    pub sum: i32,
+
+    pub kept: bool,
+    pub bonus: bool,
+    pub hit: bool,
+    pub penalty: bool,
+}
+
+/// Decimal is a number with up to three decimal places, kept in
+/// thousandths so that rolls stay whole numbers (e.g. the `1.5` of
+/// `2d6++1.5`).
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::results::Decimal;
+/// assert_eq!(format!("{}", Decimal(1500)), "1.5");
+/// assert_eq!(format!("{}", Decimal(250)), "0.25");
+/// assert_eq!(format!("{}", Decimal(-2000)), "-2.0");
+/// assert_eq!(Decimal(-1500).whole(), -1);
+/// assert_eq!(Decimal(-1500).fraction(), -500);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decimal(pub i32);
+
+impl Decimal {
+    pub const SCALE: i32 = 1000;
+
+    /// whole is the part before the decimal point.
+    pub fn whole(&self) -> i32 {
+        self.0 / Decimal::SCALE
+    }
+
+    /// fraction is the part after the decimal point, in thousandths.
+    pub fn fraction(&self) -> i32 {
+        self.0 % Decimal::SCALE
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 < 0 {
+            write!(f, "-")?;
+        }
+        let n = self.0.unsigned_abs();
+        let scale = Decimal::SCALE as u32;
+        let digits = format!("{:03}", n % scale);
+        let digits = digits.trim_end_matches('0');
+        write!(f, "{}.{}", n / scale, if digits.is_empty() { "0" } else { digits })
+    }
+}
+
+/// RoundingMode is how a fractional total is made whole.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoundingMode {
+    /// toward negative infinity
+    Down,
+    /// toward positive infinity
+    Up,
+    /// to the closest whole number, halves away from zero
+    Nearest,
+    /// dropping the fraction
+    TowardZero,
+}
+
+impl RoundingMode {
+    /// round divides `n` by `d` (which must be positive) and rounds the
+    /// quotient.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::results::RoundingMode;
+    /// assert_eq!(RoundingMode::Down.round(-7, 2), -4);
+    /// assert_eq!(RoundingMode::Up.round(-7, 2), -3);
+    /// assert_eq!(RoundingMode::Nearest.round(-7, 2), -4);
+    /// assert_eq!(RoundingMode::Nearest.round(5, 3), 2);
+    /// assert_eq!(RoundingMode::TowardZero.round(-7, 2), -3);
+    /// ```
+    pub fn round(&self, n: i64, d: i64) -> i32 {
+        let q = match self {
+            RoundingMode::Down => n.div_euclid(d),
+            RoundingMode::Up => -(-n).div_euclid(d),
+            RoundingMode::Nearest => (n + n.signum() * (d / 2)) / d,
+            RoundingMode::TowardZero => n / d,
+        };
+        q.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
 }
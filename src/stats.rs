@@ -0,0 +1,1095 @@
+use super::generators::{
+    ArithOp, ComparisonOp, Count, Die, ExprGenerator, Generator, HitsGenerator, PoolGenerator, PoolOp,
+    SuccGenerator, SuccessOp, TargetOp, TermGenerator, MAX_ROLLED_COUNT,
+};
+use super::results::{Decimal, Pool, Value};
+use rand::rngs::mock::StepRng;
+use rand::Rng;
+use std::collections::BTreeMap;
+
+/// MAX_OUTCOMES limits how much work `distribution` will do for a single
+/// dice pool. A pool without operators is convolved one die at a time and
+/// the limit is on (dice x totals x faces); a pool with operators has every
+/// combination of its faces replayed and the limit is on the number of
+/// combinations. Pools over the limit have no distribution. `20d6` is well
+/// under the limit; `100d100` and `8d6^3` are over it.
+pub const MAX_OUTCOMES: usize = 1_000_000;
+
+/// EvalMode is the face every die is treated as rolling by
+/// `Generator::evaluate`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EvalMode {
+    Min,
+    Max,
+    Average,
+}
+
+impl Generator {
+    /// distribution calculates the exact probability of every total the
+    /// generator can produce (the value returned by `Results::sum`).
+    /// Dice pools can use any of the operators that never roll another die
+    /// (e.g. keep, drop, add each or cap each); pools that explode, reroll or
+    /// roll with advantage, and pools over `MAX_OUTCOMES`, return `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("2d6").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert_eq!(dist.len(), 11);
+    /// assert!((dist[&7] - 6.0 / 36.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("1d20 >= 11").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert!((dist[&1] - 0.5).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("4dF").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert!((dist[&4] - 1.0 / 81.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("1d[0,0,1,1,2,3]").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert!((dist[&0] - 1.0 / 3.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("1d6 * 2").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert_eq!(dist.keys().copied().collect::<Vec<_>>(), vec![2, 4, 6, 8, 10, 12]);
+    ///
+    /// // the best 3 of 4d6 totals 18 only when at least three dice are 6
+    /// let gen = dice_nom::parse("4d6^3").unwrap();
+    /// let dist = gen.distribution().unwrap();
+    /// assert_eq!(dist.len(), 16);
+    /// assert!((dist[&18] - 21.0 / 1296.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("20d6").unwrap();
+    /// assert_eq!(gen.distribution().unwrap().len(), 101);
+    ///
+    /// assert_eq!(dice_nom::parse("100d100").unwrap().distribution(), None);
+    /// assert_eq!(dice_nom::parse("8d6^3").unwrap().distribution(), None);
+    ///
+    /// let gen = dice_nom::parse("4d6!!").unwrap();
+    /// assert_eq!(gen.distribution(), None);
+    /// ```
+    pub fn distribution(&self) -> Option<BTreeMap<i32, f64>> {
+        let lhs = self.succ.distribution()?;
+        let op = match &self.op {
+            Some(op) => op,
+            None => return Some(lhs),
+        };
+
+        let rhs = op.succ().distribution()?;
+        let kind = op.kind();
+        let mut dist = BTreeMap::new();
+        for (a, pa) in lhs.iter() {
+            for (b, pb) in rhs.iter() {
+                *dist.entry(kind.compare(*a, *b)).or_insert(0.0) += pa * pb;
+            }
+        }
+        Some(dist)
+    }
+
+    /// mode returns the most likely total of the generator. When several
+    /// totals are equally likely the lowest one is returned. Expressions
+    /// without a `distribution` return `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("2d6").unwrap();
+    /// assert_eq!(gen.mode(), Some(7));
+    ///
+    /// let gen = dice_nom::parse("1d6").unwrap();
+    /// assert_eq!(gen.mode(), Some(1));
+    /// ```
+    pub fn mode(&self) -> Option<i32> {
+        let dist = self.distribution()?;
+        let mut mode: Option<(i32, f64)> = None;
+        for (&k, &p) in dist.iter() {
+            match mode {
+                Some((_, max)) if p <= max => (),
+                _ => mode = Some((k, p)),
+            }
+        }
+        mode.map(|(k, _)| k)
+    }
+
+    /// max_possible returns the largest total the generator can produce.
+    /// Expressions without a `distribution` return `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("3d6 + 2").unwrap();
+    /// assert_eq!(gen.max_possible(), Some(20));
+    ///
+    /// let gen = dice_nom::parse("3d6!!").unwrap();
+    /// assert_eq!(gen.max_possible(), None);
+    ///
+    /// // a die with more faces than `MAX_OUTCOMES` has no distribution
+    /// let gen = dice_nom::parse("1d2000000000").unwrap();
+    /// assert_eq!(gen.max_possible(), None);
+    /// ```
+    pub fn max_possible(&self) -> Option<i32> {
+        self.distribution()?.keys().next_back().copied()
+    }
+
+    /// expected_value calculates the average total of the generator without
+    /// rolling any dice. Anything with a `distribution` is exact. Sums of
+    /// pools with a single explode each (`*`), explode each until (`**`),
+    /// explode on (`!=`) or compound (`!c`) operator are also calculated; each die is treated as
+    /// exploding forever, a geometric series, so very long explosions that
+    /// would be stopped by `MAX_ITERATIONS` are slightly overcounted. Other
+    /// operators return `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("3d6").unwrap();
+    /// assert_eq!(gen.expected_value(), Some(10.5));
+    ///
+    /// let gen = dice_nom::parse("1d6** + 2").unwrap();
+    /// assert!((gen.expected_value().unwrap() - 6.2).abs() < 1e-9);
+    ///
+    /// // exploding on a 1 is as likely as on a 6, so the mean is the same
+    /// let gen = dice_nom::parse("1d6!=[1] + 2").unwrap();
+    /// assert!((gen.expected_value().unwrap() - 6.2).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("5d6Y").unwrap();
+    /// assert!(gen.expected_value().is_some());
+    ///
+    /// let gen = dice_nom::parse("3d6!!").unwrap();
+    /// assert_eq!(gen.expected_value(), None);
+    /// ```
+    pub fn expected_value(&self) -> Option<f64> {
+        self.moments().map(|(mean, _)| mean)
+    }
+
+    /// variance calculates the variance of the total of the generator for
+    /// the same expressions as `expected_value`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("1d6").unwrap();
+    /// assert!((gen.variance().unwrap() - 35.0 / 12.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("2d6 - 3").unwrap();
+    /// assert!((gen.variance().unwrap() - 35.0 / 6.0).abs() < 1e-9);
+    ///
+    /// // a large die is not listed face by face
+    /// let gen = dice_nom::parse("1d2000000000").unwrap();
+    /// assert_eq!(gen.expected_value(), Some(1000000000.5));
+    /// assert!(gen.variance().unwrap() > 3.3e17);
+    /// ```
+    pub fn variance(&self) -> Option<f64> {
+        self.moments().map(|(_, var)| var)
+    }
+
+    fn moments(&self) -> Option<(f64, f64)> {
+        let sum = match (&self.op, &self.succ.op, &self.succ.hits.op) {
+            (None, None, None) => self.succ.hits.expr.moments(),
+            _ => None,
+        };
+
+        match sum {
+            Some(sum) => Some(sum),
+            None => self.distribution().map(|dist| dist_moments(&dist)),
+        }
+    }
+
+    /// explain_probability describes the chance of each outcome of a
+    /// comparison. Generators without a comparison or without a
+    /// `distribution` return `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("1d20 >= 11").unwrap();
+    /// assert_eq!(gen.explain_probability(), Some("50.0% success, 50.0% failure".to_string()));
+    ///
+    /// let gen = dice_nom::parse("1d20 + 5 >= 15").unwrap();
+    /// assert_eq!(gen.explain_probability(), Some("55.0% success, 45.0% failure".to_string()));
+    ///
+    /// let gen = dice_nom::parse("1d4 <=> 1d4").unwrap();
+    /// assert_eq!(
+    ///     gen.explain_probability(),
+    ///     Some("37.5% greater, 25.0% equal, 37.5% less".to_string())
+    /// );
+    ///
+    /// assert_eq!(dice_nom::parse("1d20").unwrap().explain_probability(), None);
+    /// ```
+    pub fn explain_probability(&self) -> Option<String> {
+        let op = self.op.as_ref()?;
+        let dist = self.distribution()?;
+        let pct = |k: i32| dist.get(&k).copied().unwrap_or(0.0) * 100.0;
+        match op {
+            ComparisonOp::CMP(_) => Some(format!(
+                "{:.1}% greater, {:.1}% equal, {:.1}% less",
+                pct(1),
+                pct(0),
+                pct(-1)
+            )),
+            _ => Some(format!("{:.1}% success, {:.1}% failure", pct(1), pct(0))),
+        }
+    }
+
+    /// evaluate calculates the total of the generator without rolling any
+    /// dice: `Min` and `Max` are the lowest and highest totals when every
+    /// die rolls its lowest or highest face (a whole number unless there is
+    /// a decimal constant) and `Average` treats every die as rolling its
+    /// mean face. Unlike `expected_value` this is not the mean of every
+    /// total (`4d6^3` is 10.5, three dice of 3.5) and a divided average
+    /// is not rounded. Operators that roll
+    /// more dice (explosions, rerolls or advantage) are left out, so every
+    /// die counts once. Comparisons, success and target operators and pool
+    /// operators that depend on the faces rolled (e.g. keep unique) return
+    /// `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::stats::EvalMode;
+    /// let gen = dice_nom::parse("2d6 + 3").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Min), Some(5.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(15.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Average), Some(10.0));
+    ///
+    /// // the lowest total subtracts the highest roll
+    /// let gen = dice_nom::parse("1d6 - 1d4").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Min), Some(-3.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(5.0));
+    ///
+    /// let gen = dice_nom::parse("4d6^3 + 4dF * 2").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Min), Some(-5.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(26.0));
+    ///
+    /// let gen = dice_nom::parse("(1d4)d6 / 2").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Min), Some(0.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(12.0));
+    /// assert_eq!(gen.evaluate(EvalMode::Average), Some(4.375));
+    ///
+    /// // explosions are left out
+    /// let gen = dice_nom::parse("3d6!!").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(18.0));
+    ///
+    /// let gen = dice_nom::parse("1d2000000000").unwrap();
+    /// assert_eq!(gen.evaluate(EvalMode::Max), Some(2000000000.0));
+    ///
+    /// assert_eq!(dice_nom::parse("1d20 >= 11").unwrap().evaluate(EvalMode::Max), None);
+    /// assert_eq!(dice_nom::parse("4d6u1").unwrap().evaluate(EvalMode::Max), None);
+    /// ```
+    pub fn evaluate(&self, mode: EvalMode) -> Option<f64> {
+        let (low, high) = match (&self.op, &self.succ.op, &self.succ.hits.op) {
+            (None, None, None) => self.succ.hits.expr.span(mode == EvalMode::Average)?,
+            _ => return None,
+        };
+        match mode {
+            EvalMode::Min => Some(low),
+            EvalMode::Max | EvalMode::Average => Some(high),
+        }
+    }
+}
+
+impl SuccGenerator {
+    fn distribution(&self) -> Option<BTreeMap<i32, f64>> {
+        let dist = self.hits.distribution()?;
+        match &self.op {
+            Some(SuccessOp::TargetSucc(n)) => {
+                let n = *n;
+                Some(map_keys(&dist, |sum| if sum >= n { sum - n + 1 } else { 0 }))
+            }
+            Some(SuccessOp::TargetSuccNext(n, m)) => {
+                let (n, m) = (*n, *m);
+                Some(map_keys(&dist, |sum| if sum >= n { ((sum - n) / m) + 1 } else { 0 }))
+            }
+            Some(SuccessOp::Tiers(tiers)) => Some(map_keys(&dist, |sum| SuccessOp::tier(tiers, sum))),
+            // successes are counted on each die, not on the sum
+            Some(SuccessOp::PerDie(_, _)) => None,
+            None => Some(dist),
+        }
+    }
+}
+
+impl HitsGenerator {
+    /// hit_distribution calculates the probability of each number of hits
+    /// for an expression with a target operator. Every die is a hit or a
+    /// miss independently, so this is exact. Expressions without a target
+    /// operator, that subtract hits, or without a `distribution` return
+    /// `None`.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// let gen = dice_nom::parse("3d6[5]").unwrap();
+    /// let dist = gen.succ.hits.hit_distribution().unwrap();
+    /// let expected = [8.0 / 27.0, 12.0 / 27.0, 6.0 / 27.0, 1.0 / 27.0];
+    /// assert_eq!(dist.len(), 4);
+    /// for (hits, p) in expected.iter().enumerate() {
+    ///     assert!((dist[&hits] - p).abs() < 1e-9);
+    /// }
+    ///
+    /// // chance of at least 3 hits on 10d6, counting 4 or more
+    /// let gen = dice_nom::parse("10d6[4]").unwrap();
+    /// let dist = gen.succ.hits.hit_distribution().unwrap();
+    /// let p: f64 = dist.range(3..).map(|(_, p)| p).sum();
+    /// assert!((p - 968.0 / 1024.0).abs() < 1e-9);
+    ///
+    /// let gen = dice_nom::parse("2d6(2)").unwrap();
+    /// let dist = gen.succ.hits.hit_distribution().unwrap();
+    /// assert!((dist[&2] - 1.0 / 9.0).abs() < 1e-9);
+    ///
+    /// assert_eq!(dice_nom::parse("3d6").unwrap().succ.hits.hit_distribution(), None);
+    /// ```
+    pub fn hit_distribution(&self) -> Option<BTreeMap<usize, f64>> {
+        self.op.as_ref()?;
+        let dist = self.distribution()?;
+        let mut hits = BTreeMap::new();
+        for (k, p) in dist.into_iter() {
+            hits.insert(usize::try_from(k).ok()?, p);
+        }
+        Some(hits)
+    }
+
+    fn distribution(&self) -> Option<BTreeMap<i32, f64>> {
+        let face: Box<dyn Fn(i32) -> i32> = match &self.op {
+            Some(TargetOp::TargetHigh(n)) => {
+                let n = *n;
+                Box::new(move |v: i32| (v.abs() >= n) as i32)
+            }
+            Some(TargetOp::TargetLow(n)) => {
+                let n = *n;
+                Box::new(move |v: i32| (v.abs() <= n) as i32)
+            }
+            Some(TargetOp::TargetHighBotch(n)) => {
+                let n = *n;
+                Box::new(move |v: i32| (v.abs() >= n) as i32 - (v == 1) as i32)
+            }
+            None => Box::new(|v| v),
+        };
+        self.expr.distribution(&face)
+    }
+}
+
+impl ExprGenerator {
+    fn distribution(&self, face: &dyn Fn(i32) -> i32) -> Option<BTreeMap<i32, f64>> {
+        // products and quotients are grouped before they are summed, the
+        // same as `ExprGenerator::generate`
+        let mut dist = constant(0);
+        let mut product = constant(0);
+        for t in self.terms.iter() {
+            let term = t.term.distribution(face)?;
+            product = match t.op {
+                ArithOp::Mul => combine(&product, &term, |a, b| a.saturating_mul(b)),
+                ArithOp::Div(_) => {
+                    combine(&product, &term, |a, b| t.op.divide(a, b))
+                }
+                ArithOp::Sub => {
+                    dist = convolve(&dist, &product);
+                    map_keys(&term, |v| -v)
+                }
+                _ => {
+                    dist = convolve(&dist, &product);
+                    term
+                }
+            };
+        }
+        Some(convolve(&dist, &product))
+    }
+}
+
+impl ExprGenerator {
+    /// moments returns the mean and variance of a sum of terms
+    fn moments(&self) -> Option<(f64, f64)> {
+        let mut mean = 0.0;
+        let mut var = 0.0;
+        for t in self.terms.iter() {
+            let (m, v) = match &t.term {
+                TermGenerator::Pool(pg) => pg.moments()?,
+                TermGenerator::Constant(n) => (*n as f64, 0.0),
+                TermGenerator::Decimal(n) => (n.0 as f64 / Decimal::SCALE as f64, 0.0),
+                TermGenerator::Group(expr) => expr.moments()?,
+                TermGenerator::Repeat(n, expr) => {
+                    let (m, v) = expr.moments()?;
+                    let n = (*n).max(0) as f64;
+                    (n * m, n * v)
+                }
+            };
+            match t.op {
+                ArithOp::ImplicitAdd | ArithOp::Add => mean += m,
+                ArithOp::Sub => mean -= m,
+                _ => return None,
+            }
+            var += v;
+        }
+        Some((mean, var))
+    }
+}
+
+impl TermGenerator {
+    fn distribution(&self, face: &dyn Fn(i32) -> i32) -> Option<BTreeMap<i32, f64>> {
+        match self {
+            TermGenerator::Pool(pg) => pg.distribution(face),
+            TermGenerator::Constant(n) => Some(constant(face(*n))),
+            // totals are whole numbers
+            TermGenerator::Decimal(_) => None,
+            TermGenerator::Group(expr) => expr.distribution(face),
+            TermGenerator::Repeat(n, expr) => {
+                let roll = expr.distribution(face)?;
+                let mut dist = constant(0);
+                for _ in 0..*n {
+                    dist = convolve(&dist, &roll);
+                    if dist.len() > MAX_OUTCOMES {
+                        return None;
+                    }
+                }
+                Some(dist)
+            }
+        }
+    }
+}
+
+impl PoolGenerator {
+    fn distribution(&self, face: &dyn Fn(i32) -> i32) -> Option<BTreeMap<i32, f64>> {
+        if self.range < 1 || self.ops.iter().any(rolls_dice) {
+            return None;
+        }
+
+        let faces = self.faces()?;
+        if faces.is_empty() {
+            return None;
+        }
+
+        if self.ops.is_empty() {
+            self.convolve_faces(&faces, face)
+        } else {
+            self.replay_faces(&faces, face)
+        }
+    }
+
+    /// faces lists the faces of the die; a die numbered up to a range with
+    /// more faces than `MAX_OUTCOMES` has `None` rather than a list that
+    /// could be too large to allocate
+    fn faces(&self) -> Option<Vec<i32>> {
+        if let Some((lo, hi)) = numbered_faces(&self.die, self.range) {
+            if hi as i64 - lo as i64 + 1 > MAX_OUTCOMES as i64 {
+                return None;
+            }
+        }
+        Some(self.die.faces(self.range))
+    }
+
+    /// convolve_faces adds the dice one at a time
+    fn convolve_faces(&self, faces: &[i32], face: &dyn Fn(i32) -> i32) -> Option<BTreeMap<i32, f64>> {
+        let count = self.count.fixed()?.max(0) as usize;
+        let lo = faces.iter().min().copied().unwrap_or(0) as i64;
+        let hi = faces.iter().max().copied().unwrap_or(0) as i64;
+        let totals = (count as i64 * (hi - lo) + 1) as usize;
+        if count.saturating_mul(totals).saturating_mul(faces.len()) > MAX_OUTCOMES {
+            return None;
+        }
+
+        let p = 1.0 / faces.len() as f64;
+        let mut die = BTreeMap::new();
+        for &v in faces {
+            *die.entry(face(v)).or_insert(0.0) += p;
+        }
+
+        let mut dist = constant(0);
+        for _ in 0..count {
+            dist = convolve(&dist, &die);
+        }
+        Some(dist)
+    }
+
+    /// replay_faces runs the operators on every combination of faces
+    fn replay_faces(&self, faces: &[i32], face: &dyn Fn(i32) -> i32) -> Option<BTreeMap<i32, f64>> {
+        let count = self.count.fixed()?.max(0) as u32;
+        let combos = faces.len().checked_pow(count).filter(|&n| n <= MAX_OUTCOMES)?;
+
+        // none of the operators replayed here use the random number generator
+        let mut rng = StepRng::new(0, 0);
+        let p = 1.0 / combos as f64;
+        let mut dist = BTreeMap::new();
+        for combo in 0..combos {
+            let mut idx = combo;
+            let mut pool = Pool::new();
+            for _ in 0..count {
+                let v = faces[idx % faces.len()];
+                idx /= faces.len();
+                pool.values.push(Value::random_with_value(v, self.range, false));
+                for op in self.ops.iter() {
+                    op.apply_last(&mut pool, &mut rng);
+                }
+            }
+            for op in self.ops.iter() {
+                op.apply_all(&mut pool, &mut rng);
+            }
+
+            let total = if pool.has_value() {
+                pool.value()
+            } else {
+                pool.values.iter().filter(|v| !v.is_discarded()).map(|v| face(v.sum())).sum()
+            };
+            *dist.entry(total).or_insert(0.0) += p;
+        }
+        Some(dist)
+    }
+}
+
+impl PoolGenerator {
+    /// moments returns the mean and variance of the pool sum
+    fn moments(&self) -> Option<(f64, f64)> {
+        // a numbered die has the mean (lo + hi) / 2 and the variance
+        // (n^2 - 1) / 12 without listing its faces
+        if let (Some((lo, hi)), []) = (numbered_faces(&self.die, self.range), self.ops.as_slice()) {
+            let n = hi as f64 - lo as f64 + 1.0;
+            if n < 1.0 {
+                return None;
+            }
+            let count = self.count.fixed()? as f64;
+            return Some((count * (lo as f64 + hi as f64) / 2.0, count * (n * n - 1.0) / 12.0));
+        }
+
+        let faces = self.faces()?;
+        if faces.is_empty() {
+            return None;
+        }
+
+        // first and second moments of a single roll, and of the part of a
+        // single roll that meets an explosion threshold
+        let n = faces.len() as f64;
+        let moment = |pred: &dyn Fn(i32) -> bool, pow: i32| -> f64 {
+            faces.iter().filter(|&&v| pred(v)).map(|&v| (v as f64).powi(pow)).sum::<f64>() / n
+        };
+        let m1 = moment(&|_| true, 1);
+        let m2 = moment(&|_| true, 2);
+        let (mean, sq) = match self.ops.as_slice() {
+            [] => (m1, m2),
+            [PoolOp::ExplodeEach(t)] => {
+                let t = t.unwrap_or(self.range);
+                let p = moment(&|v| v >= t, 0);
+                let e = moment(&|v| v >= t, 1);
+                // X = Y + I * Y'
+                (m1 + p * m1, m2 + 2.0 * e * m1 + p * m2)
+            }
+            [PoolOp::ExplodeEachUntil(t)] | [PoolOp::Compound(t)] => {
+                let t = t.unwrap_or(self.range);
+                let p = moment(&|v| v >= t, 0);
+                if p >= 1.0 {
+                    return None;
+                }
+                let e = moment(&|v| v >= t, 1);
+                // X = Y + I * X'
+                let mean = m1 / (1.0 - p);
+                (mean, (m2 + 2.0 * e * mean) / (1.0 - p))
+            }
+            [PoolOp::ExplodeOn(values)] => {
+                let p = moment(&|v| values.contains(&v), 0);
+                if p >= 1.0 {
+                    return None;
+                }
+                let e = moment(&|v| values.contains(&v), 1);
+                let mean = m1 / (1.0 - p);
+                (mean, (m2 + 2.0 * e * mean) / (1.0 - p))
+            }
+            _ => return None,
+        };
+
+        let count = self.count.fixed()? as f64;
+        Some((count * mean, count * (sq - mean * mean)))
+    }
+}
+
+impl ExprGenerator {
+    /// span returns the lowest and highest value of the expression when
+    /// every die rolls its lowest or highest face; with `average` both are
+    /// the value when every die rolls its mean face
+    fn span(&self, average: bool) -> Option<(f64, f64)> {
+        // grouped the same as `ExprGenerator::generate`
+        let mut total = (0.0, 0.0);
+        let mut product = (0.0, 0.0);
+        for t in self.terms.iter() {
+            let term = t.term.span(average)?;
+            product = match t.op {
+                ArithOp::Mul => bounds(&[
+                    product.0 * term.0,
+                    product.0 * term.1,
+                    product.1 * term.0,
+                    product.1 * term.1,
+                ]),
+                ArithOp::Div(_) if average => {
+                    let quotient = if term.0 == 0.0 { 0.0 } else { product.0 / term.0 };
+                    (quotient, quotient)
+                }
+                ArithOp::Div(_) => {
+                    // the quotient is furthest from zero for the divisors
+                    // closest to it
+                    let (lo, hi) = (term.0 as i32, term.1 as i32);
+                    let divisors: Vec<i32> = [lo, hi, -1, 1]
+                        .into_iter()
+                        .filter(|&d| d != 0 && lo <= d && d <= hi)
+                        .collect();
+                    let divisors = if divisors.is_empty() { vec![0] } else { divisors };
+                    let quotients: Vec<f64> = [product.0 as i32, product.1 as i32]
+                        .into_iter()
+                        .flat_map(|n| divisors.iter().map(move |&d| t.op.divide(n, d) as f64))
+                        .collect();
+                    bounds(&quotients)
+                }
+                ArithOp::Sub => {
+                    total = (total.0 + product.0, total.1 + product.1);
+                    (-term.1, -term.0)
+                }
+                _ => {
+                    total = (total.0 + product.0, total.1 + product.1);
+                    term
+                }
+            };
+        }
+        Some((total.0 + product.0, total.1 + product.1))
+    }
+}
+
+impl TermGenerator {
+    fn span(&self, average: bool) -> Option<(f64, f64)> {
+        match self {
+            TermGenerator::Pool(pg) => pg.span(average),
+            TermGenerator::Constant(n) => Some((*n as f64, *n as f64)),
+            TermGenerator::Decimal(n) => {
+                let n = n.0 as f64 / Decimal::SCALE as f64;
+                Some((n, n))
+            }
+            TermGenerator::Group(expr) => expr.span(average),
+            TermGenerator::Repeat(n, expr) => {
+                let (lo, hi) = expr.span(average)?;
+                let n = (*n).max(0) as f64;
+                Some((n * lo, n * hi))
+            }
+        }
+    }
+}
+
+impl PoolGenerator {
+    /// span returns the lowest and highest total of the pool when every
+    /// die rolls the same face
+    fn span(&self, average: bool) -> Option<(f64, f64)> {
+        // a numbered die is not listed face by face
+        let (lo, hi, mean) = match numbered_faces(&self.die, self.range) {
+            Some((lo, hi)) if lo <= hi => (lo as f64, hi as f64, (lo as f64 + hi as f64) / 2.0),
+            Some(_) => return None,
+            None => {
+                let faces = self.die.faces(self.range);
+                let (lo, hi) = (*faces.iter().min()?, *faces.iter().max()?);
+                let mean = faces.iter().map(|&v| v as f64).sum::<f64>() / faces.len() as f64;
+                (lo as f64, hi as f64, mean)
+            }
+        };
+        let faces = if average { (mean, mean) } else { (lo, hi) };
+
+        let max = MAX_ROLLED_COUNT as f64;
+        let counts = match &self.count {
+            Count::Fixed(n) => (*n as f64, *n as f64),
+            Count::Rolled(term) => {
+                let (lo, hi) = term.span(average)?;
+                (lo.clamp(0.0, max), hi.clamp(0.0, max))
+            }
+        };
+
+        // the total moves one way as the face or the count grows, so the
+        // lowest and highest totals are at the corners
+        let totals = [
+            self.total(counts.0, faces.0)?,
+            self.total(counts.0, faces.1)?,
+            self.total(counts.1, faces.0)?,
+            self.total(counts.1, faces.1)?,
+        ];
+        Some(bounds(&totals))
+    }
+
+    /// total is the sum of `count` dice that all roll `face`
+    fn total(&self, count: f64, face: f64) -> Option<f64> {
+        let mut kept = count.max(0.0);
+        let mut face = face;
+        let mut add = 0.0;
+        let mut single = false;
+        for op in self.ops.iter() {
+            match op {
+                PoolOp::FloorEach(n) => face = face.max(*n as f64),
+                PoolOp::CapEach(n) => face = face.min(*n as f64),
+                PoolOp::AddEach(n) => add += n.unwrap_or(1) as f64,
+                PoolOp::SubEach(n) => add -= n.unwrap_or(1) as f64,
+                PoolOp::AddFraction(n) => add += n.0 as f64 / Decimal::SCALE as f64,
+                PoolOp::TakeMid(n) | PoolOp::TakeMidLow(n) => kept = kept.min((*n).max(0) as f64),
+                // taking or dropping always keeps a die
+                PoolOp::TakeLow(n) | PoolOp::TakeHigh(n) => kept = kept.min((*n).max(1) as f64),
+                PoolOp::TakeLowPercent(pct) | PoolOp::TakeHighPercent(pct) => {
+                    // rounded the same as the operator
+                    kept = kept.min(((kept * *pct as f64 + 50.0) / 100.0).floor().max(1.0))
+                }
+                PoolOp::DropLow(n) | PoolOp::DropHigh(n) => {
+                    kept = (kept - (*n).max(0) as f64).max(kept.min(1.0))
+                }
+                PoolOp::TakeMin | PoolOp::TakeMax => single = true,
+                // only explosions are capped, and they are left out
+                PoolOp::Sort(_) | PoolOp::CapTotal(_) => (),
+                op if rolls_dice(op) => (),
+                _ => return None,
+            }
+        }
+
+        let each = face + add;
+        match single {
+            true if kept > 0.0 => Some(each),
+            true => Some(0.0),
+            false => Some(kept * each),
+        }
+    }
+}
+
+/// numbered_faces returns the lowest and highest face of a die numbered
+/// from one face up to its range, so its faces need not be listed
+fn numbered_faces(die: &Die, range: i32) -> Option<(i32, i32)> {
+    match die {
+        Die::Standard | Die::Percentile => Some((1, range)),
+        Die::Ranged(low) => Some((*low, range)),
+        _ => None,
+    }
+}
+
+/// bounds returns the lowest and highest of the values
+fn bounds(values: &[f64]) -> (f64, f64) {
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (lo, hi)
+}
+
+/// rolls_dice is true for the operators that can roll more dice, so the
+/// outcome of the pool is not decided by the faces first rolled
+fn rolls_dice(op: &PoolOp) -> bool {
+    !matches!(
+        op,
+        PoolOp::AddEach(_)
+            | PoolOp::SubEach(_)
+            | PoolOp::FloorEach(_)
+            | PoolOp::CapEach(_)
+            | PoolOp::TakeMid(_)
+            | PoolOp::TakeMidLow(_)
+            | PoolOp::TakeLow(_)
+            | PoolOp::TakeHigh(_)
+            | PoolOp::TakeLowPercent(_)
+            | PoolOp::TakeHighPercent(_)
+            | PoolOp::KeepBestPerGroup(_, _)
+            | PoolOp::DropLow(_)
+            | PoolOp::DropHigh(_)
+            | PoolOp::Sort(_)
+            | PoolOp::TakeMin
+            | PoolOp::TakeMax
+            | PoolOp::KeepUnique(_)
+            | PoolOp::DropValue(_)
+            | PoolOp::KeepValues(_)
+            | PoolOp::CountMatches(_)
+            | PoolOp::Critical
+            | PoolOp::CapTotal(_)
+            | PoolOp::BestGroup
+    )
+}
+
+fn dist_moments(dist: &BTreeMap<i32, f64>) -> (f64, f64) {
+    let mean: f64 = dist.iter().map(|(&k, &p)| k as f64 * p).sum();
+    let var = dist.iter().map(|(&k, &p)| (k as f64 - mean).powi(2) * p).sum();
+    (mean, var)
+}
+
+fn constant(n: i32) -> BTreeMap<i32, f64> {
+    let mut dist = BTreeMap::new();
+    dist.insert(n, 1.0);
+    dist
+}
+
+fn map_keys<F: Fn(i32) -> i32>(dist: &BTreeMap<i32, f64>, f: F) -> BTreeMap<i32, f64> {
+    let mut mapped = BTreeMap::new();
+    for (k, p) in dist.iter() {
+        *mapped.entry(f(*k)).or_insert(0.0) += p;
+    }
+    mapped
+}
+
+fn convolve(a: &BTreeMap<i32, f64>, b: &BTreeMap<i32, f64>) -> BTreeMap<i32, f64> {
+    combine(a, b, |ka, kb| ka + kb)
+}
+
+fn combine<F: Fn(i32, i32) -> i32>(
+    a: &BTreeMap<i32, f64>,
+    b: &BTreeMap<i32, f64>,
+    f: F,
+) -> BTreeMap<i32, f64> {
+    let mut dist = BTreeMap::new();
+    for (ka, pa) in a.iter() {
+        for (kb, pb) in b.iter() {
+            *dist.entry(f(*ka, *kb)).or_insert(0.0) += pa * pb;
+        }
+    }
+    dist
+}
+
+/// Histogram counts the totals of many rolls of a generator.
+///
+/// * Examples
+///
+/// ```
+/// use dice_nom::stats::Histogram;
+/// let gen = dice_nom::parse("2d6").unwrap();
+/// let histo = Histogram::build(&gen, 100_000, &mut rand::thread_rng());
+/// assert_eq!(histo.total(), 100_000);
+/// assert!((6..=8).contains(&histo.median().unwrap()));
+/// assert!((histo.mean().unwrap() - 7.0).abs() < 0.1);
+/// assert_eq!(histo.min(), Some(2));
+/// assert_eq!(histo.max(), Some(12));
+/// assert!((6..=8).contains(&histo.mode().unwrap()));
+///
+/// let mut histo = Histogram::new();
+/// for v in [1, 2, 2, 3, 10] {
+///     histo.add(v);
+/// }
+/// assert_eq!(histo.percentile(0.0), Some(1));
+/// assert_eq!(histo.percentile(40.0), Some(2));
+/// assert_eq!(histo.percentile(41.0), Some(2));
+/// assert_eq!(histo.percentile(61.0), Some(3));
+/// assert_eq!(histo.percentile(100.0), Some(10));
+/// assert_eq!(histo.mode(), Some(2));
+/// assert!((histo.std_dev().unwrap() - 10.64f64.sqrt()).abs() < 1e-9);
+/// assert_eq!(Histogram::new().median(), None);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Histogram {
+    counts: BTreeMap<i32, u32>,
+    total: u32,
+}
+
+impl Histogram {
+    pub fn new() -> Histogram {
+        Histogram { counts: BTreeMap::new(), total: 0 }
+    }
+
+    /// from_counts builds a histogram from the number of times each total
+    /// was rolled.
+    pub fn from_counts(counts: BTreeMap<i32, u32>) -> Histogram {
+        let total = counts.values().sum();
+        Histogram { counts, total }
+    }
+
+    /// build rolls the generator `count` times and counts each total.
+    pub fn build<R: Rng + ?Sized>(gen: &Generator, count: u32, rng: &mut R) -> Histogram {
+        let mut histo = Histogram::new();
+        for _ in 0..count {
+            histo.add(gen.generate(rng).sum());
+        }
+        histo
+    }
+
+    pub fn add(&mut self, value: i32) {
+        *self.counts.entry(value).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    /// counts is the number of times each total was rolled.
+    pub fn counts(&self) -> &BTreeMap<i32, u32> {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// max_count is the number of times the most common total was rolled.
+    pub fn max_count(&self) -> u32 {
+        self.counts.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        self.counts.keys().next().copied()
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        self.counts.keys().next_back().copied()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.total == 0 {
+            return None;
+        }
+
+        let sum: f64 = self.counts.iter().map(|(&k, &n)| k as f64 * n as f64).sum();
+        Some(sum / self.total as f64)
+    }
+
+    /// std_dev is the population standard deviation of the totals.
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let sq: f64 = self
+            .counts
+            .iter()
+            .map(|(&k, &n)| (k as f64 - mean).powi(2) * n as f64)
+            .sum();
+        Some((sq / self.total as f64).sqrt())
+    }
+
+    pub fn median(&self) -> Option<i32> {
+        self.percentile(50.0)
+    }
+
+    /// percentile returns the smallest total that at least `p` percent of
+    /// the rolls are less than or equal to (the nearest rank).
+    pub fn percentile(&self, p: f64) -> Option<i32> {
+        if self.total == 0 {
+            return None;
+        }
+
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * self.total as f64).ceil().max(1.0) as u32;
+        let mut seen = 0;
+        for (&k, &n) in self.counts.iter() {
+            seen += n;
+            if seen >= rank {
+                return Some(k);
+            }
+        }
+        self.max()
+    }
+
+    /// mode returns the most common total; ties go to the lowest total.
+    pub fn mode(&self) -> Option<i32> {
+        let max = self.max_count();
+        self.counts.iter().find(|(_, &n)| n == max).map(|(&k, _)| k)
+    }
+
+    /// render draws the histogram as one line per total from the lowest to
+    /// the highest: the total, the percent of rolls that were exactly that
+    /// total, and a bar. The longest bar is about `width` characters.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::stats::Histogram;
+    /// use std::collections::BTreeMap;
+    /// let counts: BTreeMap<i32, u32> = [(2, 1), (3, 2), (5, 1)].into_iter().collect();
+    /// let histo = Histogram::from_counts(counts);
+    /// assert_eq!(
+    ///     histo.render(50),
+    ///     "  2.  25.0: **\n  3.  50.0: ***\n  4.   0.0:\n  5.  25.0: **\n"
+    /// );
+    /// assert_eq!(
+    ///     histo.render_cumulative(50),
+    ///     "  2. 100.0: *****\n  3.  75.0: ****\n  4.  25.0: **\n  5.  25.0: **\n"
+    /// );
+    /// assert_eq!(Histogram::new().render(50), "");
+    /// ```
+    pub fn render(&self, width: usize) -> String {
+        self.render_rows(width, false)
+    }
+
+    /// render_cumulative is `render` with each line showing the percent of
+    /// rolls that were at least that total.
+    pub fn render_cumulative(&self, width: usize) -> String {
+        self.render_rows(width, true)
+    }
+
+    /// normalize buckets each total by its percent of `max`, rounded down
+    /// to a multiple of 5 and kept between 0 and 100.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::stats::Histogram;
+    /// use std::collections::BTreeMap;
+    /// let counts: BTreeMap<i32, u32> = [(3, 1), (9, 2), (18, 1)].into_iter().collect();
+    /// let histo = Histogram::from_counts(counts).normalize(18);
+    /// assert_eq!(histo.counts().iter().map(|(&k, &n)| (k, n)).collect::<Vec<_>>(), vec![(15, 1), (50, 2), (100, 1)]);
+    ///
+    /// // large totals are scaled without overflowing
+    /// let histo = Histogram::from_counts([(99_999_999, 1)].into_iter().collect());
+    /// assert_eq!(histo.normalize(100_000_000).max(), Some(95));
+    /// ```
+    pub fn normalize(&self, max: i32) -> Histogram {
+        let max = max.max(1) as i64;
+        let mut counts: BTreeMap<i32, u32> = BTreeMap::new();
+        for (&k, &n) in self.counts.iter() {
+            let pct = (k as i64 * 100 / max).clamp(0, 100) as i32;
+            *counts.entry(pct / 5 * 5).or_insert(0) += n;
+        }
+        Histogram::from_counts(counts)
+    }
+
+    /// render_normalized is `render` of the totals bucketed by their percent
+    /// of `max` (see `normalize`), with a line for every 5 percent from 0
+    /// to 100.
+    ///
+    /// * Examples
+    ///
+    /// ```
+    /// use dice_nom::stats::Histogram;
+    /// use std::collections::BTreeMap;
+    /// let counts: BTreeMap<i32, u32> = [(1, 1), (2, 1)].into_iter().collect();
+    /// let histo = Histogram::from_counts(counts);
+    /// let chart = histo.render_normalized(2, 50);
+    /// assert_eq!(chart.lines().count(), 21);
+    /// assert_eq!(chart.lines().nth(10), Some(" 50%.  50.0: **"));
+    /// assert_eq!(chart.lines().nth(11), Some(" 55%.   0.0:"));
+    /// assert_eq!(histo.render_normalized_cumulative(2, 50).lines().nth(5), Some(" 25%. 100.0: ***"));
+    /// ```
+    pub fn render_normalized(&self, max: i32, width: usize) -> String {
+        self.normalize(max).render_percents(width, false)
+    }
+
+    /// render_normalized_cumulative is `render_normalized` with each line
+    /// showing the percent of rolls that were at least that percent of `max`.
+    pub fn render_normalized_cumulative(&self, max: i32, width: usize) -> String {
+        self.normalize(max).render_percents(width, true)
+    }
+
+    fn render_percents(&self, width: usize, cumulative: bool) -> String {
+        if self.total == 0 {
+            return String::new();
+        }
+        self.render_keys((0..=100).step_by(5), "%", width, cumulative)
+    }
+
+    fn render_rows(&self, width: usize, cumulative: bool) -> String {
+        match (self.min(), self.max()) {
+            (Some(min), Some(max)) => self.render_keys(min..=max, "", width, cumulative),
+            _ => String::new(),
+        }
+    }
+
+    /// render_keys draws a line for each of `keys`, which must include
+    /// every total counted when `cumulative`
+    fn render_keys<I: Iterator<Item = i32>>(&self, keys: I, suffix: &str, width: usize, cumulative: bool) -> String {
+        // each row counts the rolls of exactly k, or of at least k when cumulative
+        let mut remaining = self.total;
+        let mut rows: Vec<(i32, u32)> = vec![];
+        for k in keys {
+            let n = self.counts.get(&k).copied().unwrap_or(0);
+            if cumulative {
+                rows.push((k, remaining));
+                remaining -= n;
+            } else {
+                rows.push((k, n));
+            }
+        }
+
+        let width = width.max(1) as u32;
+        let max_cnt = rows.iter().map(|&(_, n)| n).max().unwrap_or(0);
+        let scale = if max_cnt < width { 1 } else { max_cnt / width };
+        let mut s = String::new();
+        for (k, n) in rows {
+            let pct = (n as f64 / self.total as f64) * 100.0;
+            let label = format!("{}{}", k, suffix);
+            s.push_str(&format!("{:>w$}. {:>5.1}:", label, pct, w = 3 + suffix.len()));
+            if n > 0 {
+                s.push(' ');
+                s.push_str(&"*".repeat((n / scale + 1) as usize));
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
@@ -0,0 +1,136 @@
+use std::process::Command;
+
+fn roll(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_roll"))
+        .args(args)
+        .output()
+        .expect("failed to run roll");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn normalized_chart_spans_zero_to_one_hundred() {
+    let out = roll(&["-d", "chart", "--normalize", "-c", "1000", "3d6"]);
+    let rows: Vec<(u32, f64)> = out
+        .lines()
+        .map(|l| {
+            let (label, rest) = l.split_once("%.").unwrap();
+            let pct = rest.split(':').next().unwrap().trim();
+            (label.trim().parse().unwrap(), pct.parse().unwrap())
+        })
+        .collect();
+    assert_eq!(rows.len(), 21);
+    assert_eq!(rows.first().map(|r| r.0), Some(0));
+    assert_eq!(rows.last().map(|r| r.0), Some(100));
+
+    // 3d6 never rolls below 3, which is 1/6 of the maximum
+    assert!(rows.iter().filter(|r| r.0 < 15).all(|r| r.1 == 0.0));
+    let total: f64 = rows.iter().map(|r| r.1).sum();
+    assert!((total - 100.0).abs() < 1.5, "{}", total);
+}
+
+#[test]
+fn normalized_chart_buckets_a_large_die() {
+    let out = roll(&["-d", "chart", "--normalize", "-c", "5", "--seed", "3", "1d100000000"]);
+    let rows: Vec<f64> = out
+        .lines()
+        .map(|l| l.split_once("%.").unwrap().1.split(':').next().unwrap().trim().parse().unwrap())
+        .collect();
+    assert_eq!(rows.len(), 21);
+    let total: f64 = rows.iter().sum();
+    assert!((total - 100.0).abs() < 0.5, "{}", total);
+}
+
+#[test]
+fn normalized_chart_can_be_cumulative() {
+    let out = roll(&["-d", "chart", "--normalize", "--cumulative", "-c", "1000", "3d6"]);
+    let rows: Vec<f64> = out
+        .lines()
+        .map(|l| l.split_once("%.").unwrap().1.split(':').next().unwrap().trim().parse().unwrap())
+        .collect();
+    assert_eq!(rows.len(), 21);
+    assert_eq!(rows[0], 100.0);
+    assert!(rows.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn hide_dropped_shows_only_kept_dice() {
+    let out = roll(&["--hide-dropped", "-c", "20", "4d6^3"]);
+    for line in out.lines() {
+        let dice = line.split(": ").nth(1).unwrap().split(" = ").next().unwrap();
+        assert_eq!(dice.split(", ").count(), 3);
+        assert!(!dice.contains('-'));
+    }
+}
+
+#[test]
+fn json_display_is_an_object_or_an_array() {
+    let out = roll(&["-d", "json", "4d6^3 + 2"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["expression"], "4d6^3 + 2");
+    assert_eq!(json["dice"].as_array().unwrap().len(), 4);
+    let kept: i64 = json["dice"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|d| d["kept"] == true)
+        .map(|d| d["value"].as_i64().unwrap())
+        .sum();
+    assert_eq!(json["total"].as_i64(), Some(kept + 2));
+
+    let out = roll(&["-d", "json", "-c", "3", "1d20 >= 10"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn seed_repeats_the_whole_sequence() {
+    let first = roll(&["--seed", "42", "-c", "3", "-d", "value", "10d100"]);
+    let second = roll(&["--seed", "42", "-c", "3", "-d", "value", "10d100"]);
+    assert_eq!(first, second);
+
+    let values: Vec<&str> = first.lines().collect();
+    assert_eq!(values.len(), 3);
+    assert!(values[0] != values[1] || values[1] != values[2]);
+}
+
+#[test]
+fn stats_display_is_a_header_and_a_row() {
+    let out = roll(&["-d", "stats", "-c", "1000", "--seed", "7", "3d6"]);
+    let lines: Vec<Vec<&str>> = out.lines().map(|l| l.split_whitespace().collect()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].first(), Some(&"mean"));
+    assert_eq!(lines[1].len(), 7);
+    let min: i32 = lines[1][3].parse().unwrap();
+    let max: i32 = lines[1][6].parse().unwrap();
+    assert!(min >= 3 && max <= 18);
+}
+
+fn chart_rows(out: &str) -> Vec<(i32, f64)> {
+    out.lines()
+        .map(|l| {
+            let mut parts = l.split(':').next().unwrap().split(". ");
+            let k = parts.next().unwrap().trim().parse().unwrap();
+            let pct = parts.next().unwrap().trim().parse().unwrap();
+            (k, pct)
+        })
+        .collect()
+}
+
+#[test]
+fn chart_labels_match_each_row() {
+    // a loaded die that always rolls 2
+    let out = roll(&["-d", "chart", "-c", "100", "1d4w[0,1,0,0]"]);
+    assert_eq!(chart_rows(&out), vec![(2, 100.0)]);
+
+    let out = roll(&["-d", "chart", "-c", "1000", "--seed", "1", "1d2"]);
+    let rows = chart_rows(&out);
+    assert_eq!(rows.len(), 2);
+    assert!((rows[0].1 + rows[1].1 - 100.0).abs() < 0.2);
+
+    let out = roll(&["-d", "chart", "--cumulative", "-c", "1000", "--seed", "1", "1d2"]);
+    let cumulative = chart_rows(&out);
+    assert_eq!(cumulative[0], (1, 100.0));
+    assert_eq!(cumulative[1], rows[1]);
+}
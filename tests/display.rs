@@ -0,0 +1,164 @@
+/// one expression for every operator, plus the spacing and grouping cases
+/// that are easy to get wrong
+const EXPRESSIONS: &[&str] = &[
+    "3d6",
+    "d6",
+    "2d6 + 3",
+    "2d6 - 1d4",
+    "2d6 * 3",
+    "4d6 / 2",
+    "(3d6)\\2",
+    "(1d6 + 1d6)\\^2",
+    "7 \\~ 2",
+    "2 * (1d6 + 1d4)",
+    "2d6 + 3 * 2",
+    "10 - (6 - 1d4)",
+    "((1d4 + 1) * 2)",
+    "(1d4)d6",
+    "2d6 1d4",
+    "2d6 3",
+    "3 2d6",
+    "2 3",
+    "1d6 2x(1d4)",
+    "3d6 (1d4)",
+    "2d6 1.5",
+    "4d6^3",
+    "4d6^3 2",
+    "4d6`3",
+    "4d6kh3",
+    "4d6kl1",
+    "4d6~2",
+    "5d6~`2",
+    "10d6^50%",
+    "10d6`50%",
+    "4d6dl1",
+    "4d6dh1",
+    "4d20^^1/2",
+    "5d6u2",
+    "4d6s",
+    "4d6S 2",
+    "10d6x1",
+    "10d6k[1,6]",
+    "10d6=m4",
+    "4d6!! cap 30",
+    "2d20ADV",
+    "2d20DIS",
+    "2d20ADV 1d4",
+    "1d20ADV + 5",
+    "5d6Y",
+    "3d6!",
+    "3d6!5",
+    "3d6! 5",
+    "3d6!(1d4)",
+    "3d6!!",
+    "3d6!!5",
+    "3d6*",
+    "3d6*5",
+    "3d6**",
+    "3d6**5",
+    "3d6!=[1,6]",
+    "3d6!!:3",
+    "1d8!t2",
+    "3d6**t2:4",
+    "1d100ot5",
+    "4d6!^3",
+    "4d6! ^3",
+    "4d6!kh3",
+    "5d10**:2 + 1",
+    "3d6!(1d4):2",
+    "3d6** 2d6",
+    "3d6!c",
+    "3d6!c5",
+    "3d6!c 5",
+    "3d6p",
+    "3d6p5",
+    "3d6o",
+    "3d6o5",
+    "3d6o 4",
+    "4d6b3k2",
+    "4d6r1",
+    "4d6rr2",
+    "1d20L",
+    "1d20l",
+    "3d6_3",
+    "3d6v4",
+    "3d6$",
+    "3d6++",
+    "3d6++ 2",
+    "3d6++2",
+    "3d6--",
+    "3d6-- 2",
+    "3d6--2",
+    "3d6_3++2",
+    "2d6++1.5",
+    "2d6--0.5",
+    "1d20 + 2.5",
+    "3d6min",
+    "3d6max",
+    "10d10[8]",
+    "10d10(3)",
+    "10d10[8]b",
+    "3d8 {15}",
+    "10d6(4){3, 2}",
+    "2d8**{{4}}",
+    "2d8{{4, 2}}",
+    "2d6{7, 10, 12}",
+    "2d6{7, 10,}",
+    "1d20 {10,}",
+    "3d6 > 2d6",
+    "3d6 >= 10",
+    "3d6 < 10",
+    "3d6 <= 10",
+    "3d6 = 10",
+    "3d6 <=> 10",
+    "3x(1d20 + 5)",
+    "2x(1d6) > 7",
+    "2d6#slashing + 1d8#fire",
+    "1dF",
+    "4dF",
+    "2dcoin",
+    "1d66",
+    "2d00",
+    "1d00!!",
+    "1d%",
+    "1d%%",
+    "3d[0,0,1,1,2,3]!",
+    "3d[-1,0,1]",
+    "2d[0-9]",
+    "d[-2-1]**",
+    "1d4w[0,1,0,0]",
+];
+
+#[test]
+fn display_round_trips_through_the_parser() {
+    for &expr in EXPRESSIONS {
+        let gen = dice_nom::parse(expr).unwrap();
+        assert_eq!(dice_nom::parse_verbose(expr).as_ref(), Ok(&gen), "{}", expr);
+        let shown = format!("{}", gen);
+        assert_eq!(dice_nom::parse(&shown), Ok(gen), "{} displayed as {}", expr, shown);
+    }
+}
+
+#[test]
+fn condition_display_round_trips_through_the_parser() {
+    for expr in ["2d6 >= 8 & 1d20 >= 15", "2d6 >= 8 | 1d20 >= 15 & 1d6 2 > 3"] {
+        let cond = dice_nom::parse_condition(expr).unwrap();
+        let shown = format!("{}", cond);
+        assert_eq!(dice_nom::parse_condition(&shown), Ok(cond), "{} displayed as {}", expr, shown);
+    }
+}
+
+#[test]
+fn implicit_add_keeps_each_term_apart() {
+    let gen = dice_nom::parse("d6 d6 d6").unwrap();
+    assert_eq!(format!("{}", gen), "1d6 1d6 1d6");
+    let back = dice_nom::parse(&format!("{}", gen)).unwrap();
+    assert_eq!(back.succ.hits.expr.terms.len(), 3);
+    assert_eq!(back, gen);
+
+    // a constant is still its own term
+    let gen = dice_nom::parse("2d6 3").unwrap();
+    assert_eq!(format!("{}", gen), "2d6 3");
+    let terms = &gen.succ.hits.expr.terms;
+    assert_eq!(terms[1].term, dice_nom::generators::TermGenerator::Constant(3));
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "serde")]
+
+use dice_nom::generators::Generator;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+#[test]
+fn generator_round_trips_through_json() {
+    let gen = dice_nom::parse("2d6! + 3").unwrap();
+    let json = serde_json::to_string(&gen).unwrap();
+    let back: Generator = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, gen);
+}
+
+#[test]
+fn results_serialize_every_value() {
+    let gen = dice_nom::parse("4d6^3").unwrap();
+    let results = gen.generate(&mut StdRng::seed_from_u64(42));
+    let json = serde_json::to_value(&results).unwrap();
+    assert_eq!(json["lhs"]["values"].as_array().unwrap().len(), 4);
+    assert_eq!(json["value"], 0);
+    assert!(json["lhs"]["values"][0].get("keep").is_some());
+}
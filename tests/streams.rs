@@ -0,0 +1,35 @@
+use dice_nom::results::Results;
+use rand::rngs::StdRng;
+use std::thread;
+
+const SEED: u64 = 1234;
+const ROLLS: u64 = 1000;
+
+#[test]
+fn split_rolls_match_a_single_thread() {
+    let gen = dice_nom::parse("4d6!^3 + 2d8** - 1d4 >= 3d6").unwrap();
+    let single: Vec<Results> = (0..ROLLS).map(|n| gen.generate_streams::<StdRng>(SEED, n)).collect();
+
+    // every fourth roll on each of four threads
+    let threads = 4;
+    let mut split: Vec<(u64, Results)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let gen = &gen;
+                s.spawn(move || {
+                    (t..ROLLS)
+                        .step_by(threads as usize)
+                        .map(|n| (n, gen.generate_streams::<StdRng>(SEED, n)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    });
+    split.sort_by_key(|(n, _)| *n);
+    let split: Vec<Results> = split.into_iter().map(|(_, results)| results).collect();
+
+    assert_eq!(split, single);
+    let total = |results: &[Results]| results.iter().map(|r| r.lhs.sum() as i64).sum::<i64>();
+    assert_eq!(total(&split), total(&single));
+}